@@ -0,0 +1,102 @@
+use std::collections::BTreeSet;
+use serde::{Serialize, Deserialize};
+use crate::{
+    world::{Attr, TickInfo},
+    stats::Stats,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Achievement {
+    Glutton,
+    Handyman,
+    HighFlyer,
+    Survivor,
+    HighScorer,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 5] = [
+        Achievement::Glutton,
+        Achievement::Handyman,
+        Achievement::HighFlyer,
+        Achievement::Survivor,
+        Achievement::HighScorer,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Achievement::Glutton => "Glutton",
+            Achievement::Handyman => "Handyman",
+            Achievement::HighFlyer => "High Flyer",
+            Achievement::Survivor => "Survivor",
+            Achievement::HighScorer => "High Scorer",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::Glutton => "Eat 50 fish in one run",
+            Achievement::Handyman => "Patch 10 leaks in one run",
+            Achievement::HighFlyer => "Jump 500 units out of the water",
+            Achievement::Survivor => "Last 5 minutes in one run",
+            Achievement::HighScorer => "Score 10,000",
+        }
+    }
+}
+
+// Achievements that have ever been unlocked
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Unlocked(BTreeSet<Achievement>);
+
+impl Unlocked {
+    pub fn contains(&self, achievement: Achievement) -> bool {
+        self.0.contains(&achievement)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // True if it wasn't already unlocked
+    pub fn unlock(&mut self, achievement: Achievement) -> bool {
+        self.0.insert(achievement)
+    }
+}
+
+// Progress through a single run, built up from what happens each tick
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tracker {
+    stats: Stats,
+    achieved: BTreeSet<Achievement>,
+}
+
+impl Tracker {
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    // Anything achieved for the first time this run
+    pub fn update(&mut self, tick_info: &TickInfo, attr: &Attr) -> Vec<Achievement> {
+        self.stats.record(tick_info);
+
+        let stats = &self.stats;
+        let achieved = &mut self.achieved;
+        Achievement::ALL
+            .iter()
+            .copied()
+            .filter(|achievement| match achievement {
+                Achievement::Glutton => stats.fish_eaten >= 50,
+                Achievement::Handyman => stats.tape_delivered >= 10,
+                Achievement::HighFlyer => stats.highest_jump >= 500.0,
+                Achievement::Survivor => stats.seconds() >= 300.0 && attr.lost.is_none(),
+                Achievement::HighScorer => attr.score >= 10000.0,
+            })
+            .filter(|achievement| achieved.insert(*achievement))
+            .collect()
+    }
+}
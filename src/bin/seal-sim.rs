@@ -0,0 +1,146 @@
+use std::{env, fs, process};
+use specs::prelude::*;
+use seal_the_sub::{
+    world::{self, Attr, Event, GameMode, Inputs},
+    replay::Replay,
+    daily,
+    systems,
+    tuning::Tuning,
+};
+
+// Where the game loads it from, relative to the working directory just like the game, so that results match it unless
+// told otherwise
+const DEFAULT_TUNING: &str = "static/tuning.json";
+
+const USAGE: &str = "Usage: seal-sim [--ticks N] [--seed SEED] [--replay FILE] [--tuning FILE] [--mode endless|time-attack|zen] [--daily DAY|today]";
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(1);
+}
+
+fn load_tuning(path: &str) -> Tuning {
+    let bytes = fs::read(path).unwrap_or_else(|err| {
+        eprintln!("Could not read '{}': {}", path, err);
+        process::exit(1);
+    });
+    Tuning::from_json(&bytes).unwrap_or_else(|err| {
+        eprintln!("Could not parse tuning '{}': {}", path, err);
+        process::exit(1);
+    })
+}
+
+fn main() {
+    let mut ticks = 60 * 60;
+    let mut seed = 0;
+    let mut replay = None;
+    let mut tuning = None;
+    let mut mode = GameMode::Endless;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let val = args.next().unwrap_or_else(|| usage());
+        match arg.as_str() {
+            "--ticks" => ticks = val.parse().unwrap_or_else(|_| usage()),
+            "--seed" => seed = val.parse().unwrap_or_else(|_| usage()),
+            "--replay" => {
+                let bytes = fs::read(&val).unwrap_or_else(|err| {
+                    eprintln!("Could not read '{}': {}", val, err);
+                    process::exit(1);
+                });
+                replay = Some(Replay::from_bytes(&bytes).unwrap_or_else(|err| {
+                    eprintln!("Could not parse replay '{}': {:?}", val, err);
+                    process::exit(1);
+                }));
+            },
+            "--tuning" => tuning = Some(load_tuning(&val)),
+            // Days since the Unix epoch
+            "--daily" => seed = daily::seed(match val.as_str() {
+                "today" => daily::today(),
+                day => day.parse().unwrap_or_else(|_| usage()),
+            }),
+            "--mode" => mode = match val.as_str() {
+                "endless" => GameMode::Endless,
+                "time-attack" => GameMode::TimeAttack,
+                "zen" => GameMode::Zen,
+                _ => usage(),
+            },
+            _ => usage(),
+        }
+    }
+
+    // A replay decides both the world and the inputs, and the tuning too unless it's from before replays kept it
+    let mut inputs: Box<dyn Iterator<Item=Inputs>> = match &replay {
+        Some(replay) => {
+            seed = replay.seed;
+            mode = replay.mode;
+            if let Some(replay_tuning) = &replay.tuning {
+                tuning = Some(replay_tuning.clone());
+            }
+            ticks = replay.ticks();
+            Box::new(replay.inputs())
+        },
+        None => Box::new(std::iter::repeat(Inputs::default())),
+    };
+
+    let tuning = tuning.unwrap_or_else(|| match fs::metadata(DEFAULT_TUNING) {
+        Ok(_) => load_tuning(DEFAULT_TUNING),
+        Err(_) => {
+            eprintln!("No '{}' here, so using the built-in tuning, which may not match the game", DEFAULT_TUNING);
+            Tuning::default()
+        },
+    });
+    let (_, mut world) = world::create(seed, tuning, mode);
+    let mut dispatcher = systems::dispatcher();
+
+    let mut eat = 0;
+    let mut splash = 0;
+    let mut get_fuel = 0;
+    let mut get_tape = 0;
+    let mut bite = 0;
+    let mut ram = 0;
+    let mut sting = 0;
+    let mut clang = 0;
+    let mut explode = 0;
+    let mut breathe = 0;
+
+    let mut tick = 0;
+    while tick < ticks {
+        let time = tick as f32 * world::TICK_DURATION;
+        let tick_info = world::tick(&mut world, &mut dispatcher, inputs.next().unwrap_or_default(), time);
+        tick += 1;
+
+        for event in tick_info.events.iter() {
+            match event {
+                Event::Eat => eat += 1,
+                Event::Splash(_) => splash += 1,
+                Event::GetFuel => get_fuel += 1,
+                Event::GetTape => get_tape += 1,
+                Event::Bite => bite += 1,
+                Event::Ram => ram += 1,
+                Event::Sting => sting += 1,
+                Event::Clang => clang += 1,
+                Event::Explode(_) => explode += 1,
+                Event::Breathe => breathe += 1,
+            }
+        }
+
+        if world.read_resource::<Attr>().lost.is_some() {
+            break;
+        }
+    }
+
+    let attr = world.read_resource::<Attr>();
+    println!("seed: {}", seed);
+    println!("mode: {}", mode.name());
+    println!("ticks: {}", tick);
+    println!("attr: {:?}", *attr);
+    println!("score: {}", attr.score.floor() as u32);
+    if let Some(loss) = attr.lost {
+        println!("lost: {}", loss.describe());
+    }
+    println!(
+        "events: eat={} splash={} get_fuel={} get_tape={} bite={} ram={} sting={} clang={} explode={} breathe={}",
+        eat, splash, get_fuel, get_tape, bite, ram, sting, clang, explode, breathe,
+    );
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+use quicksilver::{
+    input::{Key, GamepadButton},
+    lifecycle::Window,
+};
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    SteerLeft,
+    SteerRight,
+    Boost,
+    Confirm,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::SteerLeft,
+        Action::SteerRight,
+        Action::Boost,
+        Action::Confirm,
+        Action::Pause,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::SteerLeft => "Steer left",
+            Action::SteerRight => "Steer right",
+            Action::Boost => "Boost",
+            Action::Confirm => "Confirm",
+            Action::Pause => "Pause",
+        }
+    }
+
+    fn default_key(&self) -> Key {
+        match self {
+            Action::SteerLeft => Key::Left,
+            Action::SteerRight => Key::Right,
+            Action::Boost => Key::Up,
+            Action::Confirm => Key::Space,
+            Action::Pause => Key::Escape,
+        }
+    }
+
+    // Gamepad buttons aren't rebindable
+    pub fn button(&self) -> GamepadButton {
+        match self {
+            Action::SteerLeft => GamepadButton::DpadLeft,
+            Action::SteerRight => GamepadButton::DpadRight,
+            Action::Boost => GamepadButton::TriggerRight,
+            Action::Confirm => GamepadButton::FaceDown,
+            Action::Pause => GamepadButton::Start,
+        }
+    }
+}
+
+// Keys that actions can be bound to
+pub const KEYS: &[Key] = &[
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+    Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::Key1, Key::Key2, Key::Key3, Key::Key4, Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9, Key::Key0,
+    Key::Numpad1, Key::Numpad2, Key::Numpad3, Key::Numpad4, Key::Numpad5,
+    Key::Numpad6, Key::Numpad7, Key::Numpad8, Key::Numpad9, Key::Numpad0,
+    Key::Left, Key::Right, Key::Up, Key::Down, Key::Space, Key::Return, Key::Escape, Key::Back, Key::Tab,
+    Key::LShift, Key::RShift, Key::LControl, Key::RControl, Key::LAlt, Key::RAlt,
+    Key::Comma, Key::Period, Key::Slash, Key::Semicolon, Key::Apostrophe, Key::LBracket, Key::RBracket,
+    Key::Minus, Key::Equals, Key::Backslash, Key::Grave,
+];
+
+// The menus use these directly, so confirm can't go on one of them without clashing
+const MENU_KEYS: &[Key] = &[
+    Key::A, Key::C, Key::D, Key::K, Key::L, Key::P, Key::R, Key::S,
+    Key::Left, Key::Right, Key::Up, Key::Down, Key::Return, Key::Escape, Key::Back,
+];
+
+// Which key each action is bound to. Anything not in here uses its default.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Controls(#[serde(with = "key_names")] BTreeMap<Action, Key>);
+
+impl Controls {
+    pub fn key(&self, action: Action) -> Key {
+        self.0.get(&action).copied().unwrap_or_else(|| action.default_key())
+    }
+
+    pub fn key_name(&self, action: Action) -> String {
+        format!("{:?}", self.key(action)).to_uppercase()
+    }
+
+    pub fn is_down(&self, window: &Window, action: Action) -> bool {
+        window.keyboard()[self.key(action)].is_down()
+            || window.gamepads().iter().any(|gamepad| gamepad[action.button()].is_down())
+    }
+
+    // An action that already had the key gets this one's old key instead, so that every action keeps one. False if
+    // that would leave confirm on one of the menu keys.
+    pub fn bind(&mut self, action: Action, key: Key) -> bool {
+        let old = self.key(action);
+        let other = Action::ALL.iter().copied().find(|other| *other != action && self.key(*other) == key);
+        let confirm = match (action, other) {
+            (Action::Confirm, _) => key,
+            (_, Some(Action::Confirm)) => old,
+            _ => self.key(Action::Confirm),
+        };
+        if MENU_KEYS.contains(&confirm) {
+            return false;
+        }
+
+        if let Some(other) = other {
+            self.0.insert(other, old);
+        }
+        self.0.insert(action, key);
+        true
+    }
+
+    pub fn reset(&mut self) {
+        self.0.clear();
+    }
+}
+
+// Keys get saved by name, since quicksilver can't serialize them itself
+mod key_names {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(keys: &BTreeMap<Action, Key>, serializer: S) -> Result<S::Ok, S::Error> {
+        keys
+            .iter()
+            .map(|(action, key)| (*action, format!("{:?}", key)))
+            .collect::<BTreeMap<_, _>>()
+            .serialize(serializer)
+    }
+
+    // Keys that aren't recognised any more fall back to their defaults
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<Action, Key>, D::Error> {
+        Ok(BTreeMap::<Action, String>::deserialize(deserializer)?
+            .into_iter()
+            .filter_map(|(action, name)| Some((action, *KEYS.iter().find(|key| format!("{:?}", key) == name)?)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binding_a_taken_key_swaps() {
+        let mut controls = Controls::default();
+        assert!(controls.bind(Action::Boost, Key::Left));
+        assert_eq!(controls.key(Action::Boost), Key::Left);
+        assert_eq!(controls.key(Action::SteerLeft), Key::Up);
+    }
+
+    #[test]
+    fn confirm_stays_off_menu_keys() {
+        let mut controls = Controls::default();
+        assert!(!controls.bind(Action::Confirm, Key::R));
+        assert!(!controls.bind(Action::SteerLeft, Key::Space));
+        assert_eq!(controls.key(Action::Confirm), Key::Space);
+        assert_eq!(controls.key(Action::SteerLeft), Key::Left);
+
+        assert!(controls.bind(Action::Confirm, Key::Z));
+        assert_eq!(controls.key(Action::Confirm), Key::Z);
+    }
+}
@@ -0,0 +1,45 @@
+// Every day has its own seed, the same for everyone, so that each day's challenge run has the same world
+
+const MS_PER_DAY: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as f64)
+        .unwrap_or(0.0)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> f64 {
+    stdweb::web::Date::now()
+}
+
+// Days since the Unix epoch in UTC, so that the day changes at the same moment everywhere
+pub fn today() -> i64 {
+    (now_ms() / MS_PER_DAY).floor() as i64
+}
+
+pub fn seed(day: i64) -> u64 {
+    // Scramble the day so that neighbouring days don't get similar seeds
+    let mut z = (day as u64) ^ 0x5EA1_7E55_DA11_u64;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// As year-month-day
+pub fn date(day: i64) -> String {
+    // Howard Hinnant's days-to-civil algorithm
+    let z = day + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{}-{:02}-{:02}", y, m, d)
+}
@@ -1,32 +1,76 @@
 use vek::*;
 use specs::prelude::*;
 use quicksilver::{
-    geom::{Rectangle, Triangle, Vector, Transform},
-    input::Key,
+    geom::{Rectangle, Triangle, Circle, Vector, Transform},
+    input::GamepadAxis,
     graphics::{Color, Background, Image, Font, FontStyle},
     sound::Sound,
     lifecycle::{Window, Event, Asset},
+    saving::{save, load, save_raw, load_raw},
+    load_file,
+    Future,
 };
 use rand::{thread_rng, prelude::*};
+use rand_pcg::Pcg32;
+use serde::{Serialize, Deserialize};
 use crate::{
     State,
     Universals,
-    world::{self, Pos, Ori, Vel, Body, Seafloor, Attr},
+    world::{self, Pos, Ori, Vel, Body, Seafloor, Attr, GameMode, Inputs, Leak},
+    daily,
+    achievements::{Achievement, Tracker},
+    leaderboard::Entry,
+    replay::Replay,
+    snapshot::{self, Snapshot},
+    tuning::Tuning,
+    systems,
     gameover::GameOver,
+    menu::Menu,
+    profiles,
+    controls::{Action, Controls},
 };
 
-#[derive(Copy, Clone, Default)]
-pub struct Inputs {
-    pub left: bool,
-    pub right: bool,
-    pub boost: bool,
+// Runs get saved this often so that closing the game doesn't lose them
+const AUTOSAVE_TICKS: u32 = world::TICKS_PER_SECOND * 10;
+const TOAST_SECONDS: f32 = 4.0;
+// How far a stick has to be tilted before it does anything
+const STICK_DEADZONE: f32 = 0.15;
+
+#[derive(Serialize, Deserialize)]
+pub struct SavedRun {
+    tick: u32,
+    replay: Replay,
+    snapshot: Snapshot,
+    #[serde(default)]
+    daily: Option<i64>,
+    #[serde(default)]
+    tracker: Tracker,
+}
+
+enum Control {
+    // Inputs come from the keyboard or a gamepad and get recorded
+    Live(Replay),
+    // Inputs come from a previously recorded run
+    Playback(Vec<Inputs>),
 }
 
 pub struct Game {
     world: world::World,
+    dispatcher: Dispatcher<'static, 'static>,
     globals: world::Globals,
+    control: Control,
+    // The day, for a daily challenge run
+    daily: Option<i64>,
+    // A replay being played back with the current tuning, as it didn't keep its own
+    untuned: bool,
+    tracker: Tracker,
+    // Newly unlocked achievements, and when they were
+    toasts: Vec<(Achievement, f32)>,
     inputs: Inputs,
+    tick: u32,
     time: f32,
+    last_tick: world::TickInfo,
+    rng: Pcg32,
 
     background: Asset<Image>,
     submarine: Asset<Image>,
@@ -46,13 +90,122 @@ pub struct Game {
 }
 
 impl Game {
-    pub fn new() -> Self {
-        let (globals, world) = world::create();
+    pub fn new(seed: Option<u64>, tuning: Tuning, mode: GameMode) -> Self {
+        let seed = seed.unwrap_or_else(|| thread_rng().gen());
+        Self::with_control(seed, tuning.clone(), mode, Control::Live(Replay::new(seed, mode, tuning)))
+    }
+
+    pub fn daily(day: i64, tuning: Tuning) -> Self {
+        let mut game = Self::new(Some(daily::seed(day)), tuning, GameMode::Endless);
+        game.daily = Some(day);
+        game
+    }
+
+    // Replays play back with the tuning they were recorded with. Older ones that didn't keep it fall back on the current
+    // tuning.json, which might not match.
+    pub fn replay(replay: &Replay, tuning: &mut Asset<Result<Tuning, String>>) -> Option<Self> {
+        let (tuning, untuned) = match &replay.tuning {
+            Some(tuning) => (tuning.clone(), false),
+            None => (Self::loaded_tuning(tuning)?.ok()?, true),
+        };
+        let mut game = Self::with_control(replay.seed, tuning, replay.mode, Control::Playback(replay.inputs().collect()));
+        game.untuned = untuned;
+        Some(game)
+    }
+
+    // Tuning gets loaded afresh for each run, so it can be tweaked while the game is open. A missing file just means
+    // the defaults, but a broken one is an error rather than a surprise.
+    pub fn load_tuning() -> Asset<Result<Tuning, String>> {
+        Asset::new(load_file("tuning.json").then(|bytes| Ok(match bytes {
+            Ok(bytes) => Tuning::from_json(&bytes).map_err(|err| err.to_string()),
+            Err(_) => Ok(Tuning::default()),
+        })))
+    }
+
+    pub fn loaded_tuning(tuning: &mut Asset<Result<Tuning, String>>) -> Option<Result<Tuning, String>> {
+        let mut loaded = None;
+        tuning.execute(|tuning| {
+            loaded = Some(tuning.clone());
+            Ok(())
+        }).ok()?;
+        loaded
+    }
+
+    pub fn last_replay(profile: u32) -> Option<Replay> {
+        load_raw("seal-the-sub", &profiles::key(profiles::REPLAY, profile))
+            .ok()
+            .and_then(|bytes| Replay::from_bytes(&bytes).ok())
+    }
+
+    pub fn resume(mut run: SavedRun) -> Option<Self> {
+        let (globals, world) = snapshot::load(run.snapshot).ok()?;
+        // Runs saved before replays kept their tuning still have it in the snapshot
+        run.replay.tuning.get_or_insert_with(|| (*world.read_resource::<Tuning>()).clone());
+        let mut game = Self::with_world(globals, world, run.replay.seed, Control::Live(run.replay));
+        game.tick = run.tick;
+        game.time = run.tick as f32 * world::TICK_DURATION;
+        game.daily = run.daily;
+        game.tracker = run.tracker;
+        Some(game)
+    }
+
+    pub fn saved_run(profile: u32) -> Option<SavedRun> {
+        load::<Option<SavedRun>>("seal-the-sub", &profiles::key(profiles::RUN, profile)).ok().and_then(|run| run)
+    }
+
+    fn save_run(&self, profile: u32) {
+        if let Control::Live(replay) = &self.control {
+            if let Ok(snapshot) = snapshot::save(&self.world) {
+                save("seal-the-sub", &profiles::key(profiles::RUN, profile), &Some(SavedRun {
+                    tick: self.tick,
+                    replay: replay.clone(),
+                    snapshot,
+                    daily: self.daily,
+                    tracker: self.tracker.clone(),
+                }));
+            }
+        }
+    }
+
+    // Keys and gamepads add together, so any of them can be used at any time. Quicksilver only reports triggers as
+    // pressed or not, so tilting the right stick gives a partial boost.
+    fn read_inputs(window: &Window, controls: &Controls) -> Inputs {
+        let deadzone = |x: f32| x.signum() * ((x.abs() - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).max(0.0);
+
+        let mut steer = controls.is_down(window, Action::SteerRight) as i32 as f32
+            - controls.is_down(window, Action::SteerLeft) as i32 as f32;
+        let mut boost = if controls.is_down(window, Action::Boost) { 1.0 } else { 0.0 };
+        for gamepad in window.gamepads() {
+            steer += deadzone(gamepad[GamepadAxis::LeftStickX]);
+            let tilt = Vec2::new(gamepad[GamepadAxis::RightStickX], gamepad[GamepadAxis::RightStickY]).magnitude();
+            boost = f32::max(boost, deadzone(tilt));
+        }
+        Inputs::new(steer, boost)
+    }
+
+    fn with_control(seed: u64, tuning: Tuning, mode: GameMode, control: Control) -> Self {
+        let (globals, world) = world::create(seed, tuning, mode);
+        Self::with_world(globals, world, seed, control)
+    }
+
+    fn with_world(globals: world::Globals, world: world::World, seed: u64, control: Control) -> Self {
         Self {
             world,
+            dispatcher: systems::dispatcher(),
             globals,
+            control,
+            daily: None,
+            untuned: false,
+            tracker: Tracker::default(),
+            toasts: Vec::new(),
             inputs: Inputs::default(),
+            tick: 0,
             time: 0.0,
+            last_tick: world::TickInfo {
+                view_scale: 1.0,
+                ..world::TickInfo::default()
+            },
+            rng: Pcg32::seed_from_u64(seed),
             background: Asset::new(Image::load("ocean.png")),
             submarine: Asset::new(Image::load("submarine.png")),
             seal: Asset::new(Image::load("seal.png")),
@@ -86,16 +239,52 @@ impl Game {
         }
     }
 
-    pub fn tick(&mut self, window: &mut Window, universals: &mut Universals) -> Option<State> {
-        let time = self.time;
-
+    pub fn update(&mut self, window: &mut Window, universals: &mut Universals) -> Option<State> {
         // Handle input
-        self.inputs.left = window.keyboard()[Key::Left].is_down();
-        self.inputs.right = window.keyboard()[Key::Right].is_down();
-        self.inputs.boost = window.keyboard()[Key::Up].is_down();
+        match &mut self.control {
+            Control::Live(_) if universals.controls.is_down(window, Action::Pause) => {
+                self.save_run(universals.profile);
+                // Zen runs never end, so this is the only chance to record their score
+                let mode = *self.world.read_resource::<GameMode>();
+                if mode == GameMode::Zen {
+                    let score = self.world.read_resource::<Attr>().score.floor() as u32;
+                    let high_score = universals.high_score(mode);
+                    *high_score = (*high_score).max(score);
+                }
+                return Some(State::Menu(Menu::new(universals.profile)));
+            },
+            Control::Live(replay) => {
+                self.inputs = Self::read_inputs(window, &universals.controls);
+                replay.record(self.inputs);
+            },
+            Control::Playback(inputs) => match inputs.get(self.tick as usize) {
+                Some(inputs) if !universals.controls.is_down(window, Action::Confirm) => self.inputs = *inputs,
+                _ => return Some(State::Menu(Menu::new(universals.profile))),
+            },
+        }
 
         // Tick world
-        let tick_info = world::tick(&self.world, self.inputs, time, &self.globals);
+        let time = self.tick as f32 * world::TICK_DURATION;
+        let tick_info = world::tick(&mut self.world, &mut self.dispatcher, self.inputs, time);
+
+        // Watching a replay shouldn't unlock anything or count towards stats, and nothing in zen mode is hard enough to
+        // earn an achievement
+        if let Control::Live(_) = self.control {
+            if self.tick == 0 {
+                universals.stats.runs += 1;
+            }
+            universals.stats.record(&tick_info);
+
+            let achieved = self.tracker.update(&tick_info, &self.world.read_resource::<Attr>());
+            if *self.world.read_resource::<GameMode>() != GameMode::Zen {
+                for achievement in achieved {
+                    if universals.achievements.unlock(achievement) {
+                        self.toasts.push((achievement, time));
+                        universals.save();
+                    }
+                }
+            }
+        }
 
         for event in tick_info.events.iter() {
             match event {
@@ -111,10 +300,75 @@ impl Game {
                 world::Event::GetTape => {
                     self.ding.execute(|ding| ding.play());
                 },
+                world::Event::Bite => {
+                    self.chomp.execute(|chomp| chomp.play());
+                },
+                world::Event::Ram | world::Event::Clang => {
+                    self.thud.execute(|thud| { thud.set_volume(1.0); thud.play() });
+                },
+                world::Event::Sting => {
+                    self.thud.execute(|thud| { thud.set_volume(0.5); thud.play() });
+                },
+                world::Event::Explode(x) if (x - tick_info.view_centre.x).abs() < 1500.0 => {
+                    self.hardsplash.execute(|hardsplash| { hardsplash.set_volume(10.0); hardsplash.play() });
+                },
                 _ => {},
             }
         }
 
+        self.tick += 1;
+        self.time = self.tick as f32 * world::TICK_DURATION;
+        self.last_tick = tick_info;
+
+        if self.tick % AUTOSAVE_TICKS == 0 {
+            self.save_run(universals.profile);
+            universals.save();
+        }
+
+        let attr = self.world.read_resource::<Attr>();
+        if let Some(loss) = attr.lost {
+            let replay = match &self.control {
+                Control::Live(replay) => replay,
+                Control::Playback(_) => return Some(State::Menu(Menu::new(universals.profile))),
+            };
+            save_raw("seal-the-sub", &profiles::key(profiles::REPLAY, universals.profile), &replay.to_bytes());
+            save("seal-the-sub", &profiles::key(profiles::RUN, universals.profile), &None::<SavedRun>);
+
+            // Daily challenge runs only compete with each other
+            let score = attr.score.floor() as u32;
+            let high_score = match self.daily {
+                Some(day) => universals.daily_bests.entry(day).or_insert(0),
+                None => universals.high_score(replay.mode),
+            };
+            *high_score = (*high_score).max(score);
+            let is_high_score = score == *high_score;
+            universals.total_score += score;
+            universals.stats.record_death(loss);
+
+            // Ask for a name if the run earned a place on the leaderboard
+            let stats = self.tracker.stats().clone();
+            let entry = if self.daily.is_none() && universals.leaderboard.qualifies(replay.mode, score) {
+                Some(Entry {
+                    name: universals.name.clone(),
+                    score,
+                    day: daily::today(),
+                    seconds: stats.seconds(),
+                    loss,
+                    mode: replay.mode,
+                })
+            } else {
+                None
+            };
+            return Some(State::GameOver(GameOver::new(is_high_score, loss, stats, entry)))
+        }
+
+        None
+    }
+
+    pub fn draw(&mut self, window: &mut Window, controls: &Controls) {
+        let time = self.time;
+        let tick_info = &self.last_tick;
+
         let world_trans = Transform::IDENTITY
             * Transform::translate((Vec2::new(window.screen_size().x, window.screen_size().y) * 0.5).into_tuple())
             * Transform::scale(Vec2::broadcast(tick_info.view_scale).into_tuple())
@@ -156,6 +410,7 @@ impl Game {
         }
 
         // Entities
+        let rng = &mut self.rng;
         for (pos, ori, vel, body) in (
             &self.world.read_storage::<Pos>(),
             &self.world.read_storage::<Ori>(),
@@ -248,19 +503,107 @@ impl Game {
                             &Rectangle::new((-512.0, -512.0), (1024.0, 1024.0)),
                             Background::Img(&submarine),
                             world_trans
-                                * Transform::rotate((time * 1.0).sin() * 3.0)
                                 * Transform::translate((
                                     pos.0 + Vec2::new(
-                                        thread_rng().gen_range(-1.0, 1.0),
-                                        thread_rng().gen_range(-1.0, 1.0) + (time * 2.0).sin() * 16.0,
+                                        rng.gen_range(-1.0, 1.0),
+                                        rng.gen_range(-1.0, 1.0) + (time * 2.0).sin() * 16.0,
                                     )
-                                ).into_tuple()),
+                                ).into_tuple())
+                                * Transform::rotate((time * 1.0).sin() * 3.0)
+                                * Transform::scale(if vel.0.x < 0.0 { (-1.0, 1.0) } else { (1.0, 1.0) }),
                             -0.5,
                         );
 
                         Ok(())
                     });
                 },
+                Body::Leak => {},
+                Body::Shark | Body::Orca => {
+                    // No sprites for these yet, so they're drawn from simple shapes
+                    let (size, colour) = match body {
+                        Body::Orca => (1.6, Color::from_rgba(20, 20, 30, 1.0)),
+                        _ => (1.0, Color::from_rgba(110, 120, 135, 1.0)),
+                    };
+                    let trans = world_trans
+                        * Transform::translate(pos.0.into_tuple())
+                        * Transform::rotate(ori.0.to_degrees())
+                        * Transform::scale(if vel.0.x > 0.0 { (size, size) } else { (size, -size) });
+                    let tail = (time * 8.0).sin() * 6.0;
+                    window.draw_ex(&Triangle::new((60.0, 0.0), (-40.0, -16.0), (-40.0, 16.0)), colour, trans, 0.0);
+                    window.draw_ex(&Triangle::new((-30.0, 0.0), (-62.0, -20.0 + tail), (-62.0, 20.0 + tail)), colour, trans, 0.0);
+                    window.draw_ex(&Triangle::new((10.0, -8.0), (-16.0, -8.0), (-14.0, -34.0)), colour, trans, 0.0);
+                    if let Body::Orca = body {
+                        window.draw_ex(&Circle::new((38.0, -4.0), 5.0), Color::WHITE, trans, 0.1);
+                    }
+                },
+                Body::Mine => {
+                    let trans = world_trans * Transform::translate(pos.0.into_tuple());
+                    for i in 0..8 {
+                        window.draw_ex(
+                            &Rectangle::new((-3.0, -32.0), (6.0, 64.0)),
+                            Color::from_rgba(40, 40, 40, 1.0),
+                            trans * Transform::rotate(i as f32 * 22.5),
+                            0.0,
+                        );
+                    }
+                    window.draw_ex(&Circle::new((0.0, 0.0), 24.0), Color::from_rgba(60, 60, 60, 1.0), trans, 0.1);
+                    if (time * 3.0).fract() < 0.5 {
+                        window.draw_ex(&Circle::new((0.0, 0.0), 5.0), Color::RED, trans, 0.2);
+                    }
+                },
+                Body::Jellyfish(i) => {
+                    let squash = 1.0 + ((time + *i as f32) * 3.0).sin() * 0.15;
+                    let trans = world_trans
+                        * Transform::translate(pos.0.into_tuple())
+                        * Transform::scale((squash, 1.0 / squash));
+                    let colour = Color::from_rgba(230, 120, 200, 0.7);
+                    for j in 0..4 {
+                        let sway = ((time * 2.0) + j as f32).sin() * 4.0;
+                        window.draw_ex(
+                            &Rectangle::new((-15.0 + j as f32 * 9.0 + sway, 0.0), (3.0, 40.0)),
+                            colour,
+                            trans,
+                            0.0,
+                        );
+                    }
+                    window.draw_ex(&Circle::new((0.0, 0.0), 24.0), colour, trans, 0.1);
+                },
+                Body::Debris(i) => {
+                    let size = 36.0 + (i % 3) as f32 * 12.0;
+                    window.draw_ex(
+                        &Rectangle::new((-size * 0.5, -12.0), (size, 24.0)),
+                        Color::from_rgba(120, 90, 60, 1.0),
+                        world_trans
+                            * Transform::translate(pos.0.into_tuple())
+                            * Transform::rotate(ori.0.to_degrees()),
+                        0.0,
+                    );
+                },
+            }
+        }
+
+        // Leaks spray bubbles out of the hull, bobbing along with it
+        for (pos, leak) in (
+            &self.world.read_storage::<Pos>(),
+            &self.world.read_storage::<Leak>(),
+        ).join() {
+            let bob = Vec2::new(0.0, (time * 2.0).sin() * 16.0);
+            let dir = leak.offset.try_normalized().unwrap_or(Vec2::unit_y());
+            for i in 0..4 {
+                let phase = (time * 2.0 + i as f32 * 0.25).fract();
+                let img_idx = i % self.bubbles.len();
+                self.bubbles[img_idx].execute(|bubble| {
+                    window.draw_ex(
+                        &Rectangle::new((-12.0, -12.0), (24.0, 24.0)),
+                        Background::Img(&bubble),
+                        world_trans
+                            * Transform::translate((pos.0 + bob + dir * phase * 60.0).into_tuple())
+                            * Transform::scale(Vec2::broadcast(0.5 + phase).into_tuple()),
+                        0.5,
+                    );
+
+                    Ok(())
+                });
             }
         }
 
@@ -288,6 +631,27 @@ impl Game {
         // UI
         let attr = self.world.read_resource::<Attr>();
 
+        // Point towards the submarine when it's out of sight
+        if let Some(sub_pos) = self.world.read_storage::<Pos>().get(self.globals.submarine) {
+            let screen_size = Vec2::new(window.screen_size().x, window.screen_size().y);
+            let offset = (sub_pos.0 - tick_info.view_centre) * tick_info.view_scale;
+            if offset.x.abs() > screen_size.x * 0.5 || offset.y.abs() > screen_size.y * 0.5 {
+                let dir = offset.try_normalized().unwrap_or(Vec2::unit_x());
+                let side = Vec2::new(-dir.y, dir.x);
+                let centre = screen_size * 0.5 + dir * (screen_size.y * 0.5 - 40.0);
+                window.draw_ex(
+                    &Triangle::new(
+                        (centre + dir * 20.0).into_tuple(),
+                        (centre - dir * 10.0 + side * 12.0).into_tuple(),
+                        (centre - dir * 10.0 - side * 12.0).into_tuple(),
+                    ),
+                    Color::from_rgba(255, 220, 50, 1.0),
+                    Transform::IDENTITY,
+                    10.0,
+                );
+            }
+        }
+
         let mut font = &mut self.font;
         let mut draw_bar = |msg, val, y: f32| {
             font.execute(|font| {
@@ -306,9 +670,76 @@ impl Game {
             });
         };
 
-        draw_bar("Stamina", attr.stamina, 16.0);
-        draw_bar("Hull", attr.hull, 48.0);
-        draw_bar("Fuel", attr.fuel, 80.0);
+        // Nothing can run out in zen mode, so there's no point showing the meters
+        let mode = *self.world.read_resource::<GameMode>();
+        if mode != GameMode::Zen {
+            draw_bar("Stamina", attr.stamina, 16.0);
+            draw_bar("Hull", attr.hull, 48.0);
+            draw_bar("Fuel", attr.fuel, 80.0);
+            draw_bar("Oxygen", attr.oxygen, 112.0);
+        }
+
+        if let Some(limit) = mode.time_limit() {
+            let left = (limit - time).max(0.0).ceil() as u32;
+            font.execute(|font| {
+                let img = font.render(&format!("{}:{:02}", left / 60, left % 60), &FontStyle::new(48.0, Color::WHITE)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((window.screen_size().x - img.area().width() - 22.0, 70.0)),
+                    10.0,
+                );
+
+                Ok(())
+            });
+        }
+
+        // Achievement toasts slide in at the top, one after another
+        self.toasts.retain(|(_, shown)| time - shown < TOAST_SECONDS);
+        for (i, (achievement, shown)) in self.toasts.iter().enumerate() {
+            let slide = ((time - shown) * 4.0).min(1.0).min((TOAST_SECONDS - (time - shown)) * 4.0);
+            let y = -60.0 + slide * (76.0 + i as f32 * 64.0);
+            font.execute(|font| {
+                let title = font.render(&format!("Achievement unlocked: {}", achievement.name()), &FontStyle::new(32.0, Color::WHITE)).unwrap();
+                let desc = font.render(achievement.description(), &FontStyle::new(24.0, Color::WHITE)).unwrap();
+                let width = title.area().width().max(desc.area().width()) + 24.0;
+                let x = (window.screen_size().x - width) * 0.5;
+                window.draw_ex(&Rectangle::new((x, y), (width, 58.0)), Color::from_rgba(20, 40, 80, 0.85), Transform::IDENTITY, 11.0);
+                window.draw_ex(&title.area(), Background::Img(&title), Transform::translate((x + 12.0, y + 2.0)), 12.0);
+                window.draw_ex(&desc.area(), Background::Img(&desc), Transform::translate((x + 12.0, y + 32.0)), 12.0);
+
+                Ok(())
+            });
+        }
+
+        let untuned = self.untuned;
+        let hint = format!("Replay - press {} to stop", controls.key_name(Action::Confirm));
+        if let Control::Playback(_) = self.control {
+            font.execute(|font| {
+                let img = font.render(&hint, &FontStyle::new(32.0, Color::WHITE)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((22.0, window.screen_size().y - img.area().height() - 22.0)),
+                    10.0,
+                );
+
+                if untuned {
+                    let img = font.render(
+                        "Recorded before replays kept their tuning, so this may not match the original run",
+                        &FontStyle::new(24.0, Color::WHITE),
+                    ).unwrap();
+                    window.draw_ex(
+                        &img.area(),
+                        Background::Img(&img),
+                        Transform::translate((22.0, window.screen_size().y - img.area().height() - 64.0)),
+                        10.0,
+                    );
+                }
+
+                Ok(())
+            });
+        }
 
         font.execute(|font| {
             let img = font.render(&format!("Score: {}", attr.score.floor() as u32), &FontStyle::new(48.0, Color::WHITE)).unwrap();
@@ -322,14 +753,5 @@ impl Game {
             Ok(())
         });
 
-        self.time = time + 1.0 / 60.0;
-
-        if attr.lost {
-            universals.high_score = universals.high_score.max(attr.score.floor() as u32);
-            universals.total_score += attr.score.floor() as u32;
-            return Some(State::GameOver(GameOver::new(attr.score.floor() as u32 == universals.high_score)))
-        }
-
-        None
     }
 }
@@ -2,7 +2,7 @@ use vek::*;
 use specs::prelude::*;
 use quicksilver::{
     geom::{Rectangle, Triangle, Vector, Transform},
-    input::{Key, ButtonState},
+    input::Key,
     graphics::{Color, Background, Image, Font, FontStyle},
     sound::Sound,
     lifecycle::{Window, Event, Asset},
@@ -11,14 +11,32 @@ use rand::{thread_rng, prelude::*};
 use crate::{
     State,
     Universals,
-    world::{self, Pos, Ori, Vel, Body, Seafloor, Attr},
+    world::{self, Pos, Ori, Vel, Body, Seafloor, Attr, Loss},
     game::Game,
     menu::Menu,
+    tuning::Tuning,
+    stats::Stats,
+    leaderboard::{Entry, MAX_NAME},
+    controls::Action,
 };
 
+// Adds typed characters to a name, up to the longest one allowed
+pub fn type_name(name: &mut String, event: &Event) {
+    if let Event::Typed(c) = event {
+        if !c.is_control() && name.chars().count() < MAX_NAME {
+            name.push(*c);
+        }
+    }
+}
+
 pub struct GameOver {
     time: f32,
     is_high_score: bool,
+    loss: Loss,
+    stats: Stats,
+    // A leaderboard entry still waiting for a name
+    entry: Option<Entry>,
+    rank: Option<usize>,
 
     background: Asset<Image>,
     submarine: Asset<Image>,
@@ -30,14 +48,18 @@ pub struct GameOver {
     chomp: Asset<Sound>,
 
     font: Asset<Font>,
+    tuning: Asset<Result<Tuning, String>>,
 }
 
 impl GameOver {
-    pub fn new(is_high_score: bool) -> Self {
-        let (globals, world) = world::create();
+    pub fn new(is_high_score: bool, loss: Loss, stats: Stats, entry: Option<Entry>) -> Self {
         Self {
             time: 0.0,
             is_high_score,
+            loss,
+            stats,
+            entry,
+            rank: None,
             background: Asset::new(Image::load("ocean.png")),
             submarine: Asset::new(Image::load("submarine.png")),
             seal: Asset::new(Image::load("seal.png")),
@@ -55,6 +77,36 @@ impl GameOver {
             chomp: Asset::new(Sound::load("chomp.wav")),
 
             font: Asset::new(Font::load("font.ttf")),
+            tuning: Game::load_tuning(),
+        }
+    }
+
+    pub fn event(&mut self, event: &Event) {
+        if let Some(entry) = &mut self.entry {
+            type_name(&mut entry.name, event);
+        }
+    }
+
+    pub fn press(&mut self, key: Key, universals: &mut Universals) {
+        if let Some(entry) = &mut self.entry {
+            match key {
+                Key::Back => {
+                    entry.name.pop();
+                },
+                Key::Return if !entry.name.trim().is_empty() => {
+                    entry.name = entry.name.trim().to_string();
+                    universals.name = entry.name.clone();
+                    self.rank = universals.leaderboard.insert(entry.clone());
+                    universals.save();
+                    self.entry = None;
+                    self.time = 0.0;
+                },
+                Key::Escape => {
+                    self.entry = None;
+                    self.time = 0.0;
+                },
+                _ => {},
+            }
         }
     }
 
@@ -62,8 +114,14 @@ impl GameOver {
         let time = self.time;
 
         // Handle input
-        if window.keyboard()[Key::Space].is_down() && time > 0.5 {
-            return Some(State::Menu(Menu::new()))
+        if self.entry.is_none() && universals.controls.is_down(window, Action::Confirm) && time > 0.5 {
+            return Some(State::Menu(Menu::new(universals.profile)))
+        }
+
+        if window.keyboard()[Key::R].is_down() && time > 0.5 && self.entry.is_none() {
+            if let Some(game) = Game::last_replay(universals.profile).and_then(|replay| Game::replay(&replay, &mut self.tuning)) {
+                return Some(State::Replay(game))
+            }
         }
 
         window.clear(Color::from_rgba(120, 200, 255, 1.0));
@@ -92,6 +150,11 @@ impl GameOver {
                     });
 
         let is_high_score = self.is_high_score;
+        let loss = self.loss;
+        let stats = &self.stats;
+        let entry = &self.entry;
+        let rank = self.rank;
+        let confirm = universals.controls.key_name(Action::Confirm);
         self.font.execute(|font| {
             let img = font.render("Game Over!", &FontStyle::new(64.0, Color::WHITE)).unwrap();
             window.draw_ex(
@@ -101,8 +164,14 @@ impl GameOver {
                 10.0,
             );
 
-            if is_high_score {
-                let img = font.render("You got a high score!", &FontStyle::new(48.0, Color::from_rgba(50, 255, 150, 1.0))).unwrap();
+            let message = match (is_high_score, rank) {
+                (true, _) => Some("You got a high score!".to_string()),
+                (false, Some(rank)) => Some(format!("You made the leaderboard at #{}!", rank + 1)),
+                (false, None) if entry.is_some() => Some("You made the leaderboard!".to_string()),
+                (false, None) => None,
+            };
+            if let Some(message) = message {
+                let img = font.render(&message, &FontStyle::new(48.0, Color::from_rgba(50, 255, 150, 1.0))).unwrap();
                 window.draw_ex(
                     &img.area(),
                     Background::Img(&img),
@@ -111,7 +180,19 @@ impl GameOver {
                 );
             }
 
-            let img = font.render("Press SPACE to return to the menu", &FontStyle::new(48.0, Color::WHITE)).unwrap();
+            let (first, second) = match entry {
+                // Blink a cursor after the name
+                Some(entry) => (
+                    format!("Your name: {}{}", entry.name, if time % 1.0 < 0.5 { "_" } else { "" }),
+                    "Press ENTER to save, ESCAPE to skip".to_string(),
+                ),
+                None => (
+                    format!("Press {} to return to the menu", confirm),
+                    "Press R to watch the replay".to_string(),
+                ),
+            };
+
+            let img = font.render(&first, &FontStyle::new(48.0, Color::WHITE)).unwrap();
             window.draw_ex(
                 &img.area(),
                 Background::Img(&img),
@@ -119,6 +200,47 @@ impl GameOver {
                 10.0,
             );
 
+            let img = font.render(&second, &FontStyle::new(48.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 300.0)),
+                10.0,
+            );
+
+            let img = font.render(loss.describe(), &FontStyle::new(32.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 370.0)),
+                10.0,
+            );
+
+            let seconds = stats.seconds() as u32;
+            let summary = format!(
+                "{}:{:02} survived, {} fish eaten, {} fuel and {} tape delivered",
+                seconds / 60, seconds % 60, stats.fish_eaten, stats.fuel_delivered, stats.tape_delivered,
+            );
+            let img = font.render(&summary, &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 415.0)),
+                10.0,
+            );
+
+            let summary = format!(
+                "Deepest dive {}, highest jump {}",
+                stats.deepest_dive.floor(), stats.highest_jump.floor(),
+            );
+            let img = font.render(&summary, &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 441.0)),
+                10.0,
+            );
+
             Ok(())
         });
 
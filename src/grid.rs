@@ -0,0 +1,101 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+};
+use vek::*;
+use specs::Entity;
+
+// A body's position and radius
+type Body<T> = (T, Vec2<f32>, f32);
+
+// A uniform grid of cells, each listing the bodies that overlap it
+pub struct SpatialGrid<T = Entity> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<Body<T>>>,
+    entries: usize,
+}
+
+impl<T: Copy + Ord> SpatialGrid<T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            entries: 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries = 0;
+    }
+
+    fn cell_of(&self, pos: Vec2<f32>) -> Vec2<i32> {
+        (pos / self.cell_size).map(|e| e.floor() as i32)
+    }
+
+    fn cells_covering(&self, pos: Vec2<f32>, radius: f32) -> impl Iterator<Item=(i32, i32)> {
+        let min = self.cell_of(pos - radius);
+        let max = self.cell_of(pos + radius);
+        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| (x, y)))
+    }
+
+    pub fn insert(&mut self, item: T, pos: Vec2<f32>, radius: f32) {
+        for cell in self.cells_covering(pos, radius).collect::<Vec<_>>() {
+            self.cells.entry(cell).or_default().push((item, pos, radius));
+            self.entries += 1;
+        }
+    }
+
+    // Every body that overlaps the circle, in item order so that results don't depend on the layout of the grid
+    pub fn within(&self, pos: Vec2<f32>, radius: f32) -> Vec<T> {
+        let mut found = self.cells_covering(pos, radius)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(|(_, other_pos, other_radius)| other_pos.distance_squared(pos) < (radius + other_radius).powf(2.0))
+            .map(|(item, _, _)| *item)
+            .collect::<Vec<_>>();
+        found.sort();
+        found.dedup();
+        found
+    }
+
+    // The k items closest to a point, nearest first. Searches outwards one ring of cells at a time, so it's only
+    // meant for grids of points (a radius of zero).
+    pub fn nearest(&self, pos: Vec2<f32>, k: usize) -> Vec<T> {
+        let centre = self.cell_of(pos);
+        let by_dist = |a: &(f32, T), b: &(f32, T)| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal).then(a.1.cmp(&b.1));
+
+        let mut found: Vec<(f32, T)> = Vec::new();
+        let mut seen = 0;
+        let mut ring: i32 = 0;
+        while k > 0 && seen < self.entries {
+            let ring_cells = (-ring..=ring)
+                .flat_map(|x| (-ring..=ring).map(move |y| (x, y)))
+                .filter(|(x, y)| x.abs() == ring || y.abs() == ring);
+            for (x, y) in ring_cells {
+                if let Some(items) = self.cells.get(&(centre.x + x, centre.y + y)) {
+                    seen += items.len();
+                    found.extend(items.iter().map(|(item, other_pos, _)| (other_pos.distance_squared(pos), *item)));
+                }
+            }
+
+            found.sort_by(by_dist);
+            found.truncate(k);
+
+            // Nothing outside the rings searched so far can be closer than this
+            let reach = ring as f32 * self.cell_size;
+            if found.len() == k && found[k - 1].0 <= reach.powf(2.0) {
+                break;
+            }
+            ring += 1;
+        }
+
+        found.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+impl<T: Copy + Ord> Default for SpatialGrid<T> {
+    fn default() -> Self {
+        Self::new(128.0)
+    }
+}
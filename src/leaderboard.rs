@@ -0,0 +1,126 @@
+use serde::{Serialize, Deserialize};
+use crate::world::{GameMode, Loss};
+
+// How many entries each mode keeps
+pub const SIZE: usize = 20;
+pub const MAX_NAME: usize = 12;
+
+// Zen runs never end in a game over, so they never get a name to go on the board
+pub const MODES: [GameMode; 2] = [GameMode::Endless, GameMode::TimeAttack];
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    pub score: u32,
+    // Days since the Unix epoch
+    pub day: i64,
+    pub seconds: f32,
+    pub loss: Loss,
+    pub mode: GameMode,
+}
+
+// The best runs in each mode, highest score first
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Leaderboard(Vec<Entry>);
+
+impl Leaderboard {
+    pub fn entries(&self, mode: GameMode) -> impl Iterator<Item=&Entry> {
+        self.0.iter().filter(move |entry| entry.mode == mode)
+    }
+
+    pub fn qualifies(&self, mode: GameMode, score: u32) -> bool {
+        score > 0 && self
+            .entries(mode)
+            .nth(SIZE - 1)
+            .map(|last| score > last.score)
+            .unwrap_or(true)
+    }
+
+    // The entry's rank, or None if it didn't make it onto the board
+    pub fn insert(&mut self, entry: Entry) -> Option<usize> {
+        if !self.qualifies(entry.mode, entry.score) {
+            return None;
+        }
+
+        // Equal scores keep the earlier entry ahead
+        let mode = entry.mode;
+        let idx = self.0.iter().position(|other| other.score < entry.score).unwrap_or(self.0.len());
+        self.0.insert(idx, entry);
+
+        // Drop whatever got pushed off the bottom of this mode's board
+        if let Some(last) = self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.mode == mode)
+            .nth(SIZE)
+            .map(|(i, _)| i)
+        {
+            self.0.remove(last);
+        }
+
+        Some(self.0[..idx].iter().filter(|other| other.mode == mode).count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32, mode: GameMode) -> Entry {
+        Entry { name: name.to_string(), score, day: 0, seconds: 0.0, loss: Loss::Hull, mode }
+    }
+
+    fn names(board: &Leaderboard, mode: GameMode) -> Vec<&str> {
+        board.entries(mode).map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn highest_score_first() {
+        let mut board = Leaderboard::default();
+        assert_eq!(board.insert(entry("b", 20, GameMode::Endless)), Some(0));
+        assert_eq!(board.insert(entry("c", 10, GameMode::Endless)), Some(1));
+        assert_eq!(board.insert(entry("a", 30, GameMode::Endless)), Some(0));
+        assert_eq!(names(&board, GameMode::Endless), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn ties_keep_the_earlier_entry_ahead() {
+        let mut board = Leaderboard::default();
+        board.insert(entry("first", 10, GameMode::Endless));
+        assert_eq!(board.insert(entry("second", 10, GameMode::Endless)), Some(1));
+        assert_eq!(names(&board, GameMode::Endless), ["first", "second"]);
+    }
+
+    #[test]
+    fn zero_never_qualifies() {
+        let mut board = Leaderboard::default();
+        assert_eq!(board.insert(entry("a", 0, GameMode::Endless)), None);
+        assert_eq!(board.entries(GameMode::Endless).count(), 0);
+    }
+
+    #[test]
+    fn ranks_only_count_the_same_mode() {
+        let mut board = Leaderboard::default();
+        board.insert(entry("zen", 100, GameMode::Zen));
+        board.insert(entry("endless", 50, GameMode::Endless));
+        assert_eq!(board.insert(entry("timed", 10, GameMode::TimeAttack)), Some(0));
+        assert_eq!(board.insert(entry("endless 2", 75, GameMode::Endless)), Some(0));
+    }
+
+    #[test]
+    fn each_mode_keeps_its_own_top_entries() {
+        let mut board = Leaderboard::default();
+        for score in 1..=SIZE as u32 {
+            board.insert(entry("endless", score, GameMode::Endless));
+            board.insert(entry("zen", score, GameMode::Zen));
+        }
+        assert!(!board.qualifies(GameMode::Endless, 1));
+        assert_eq!(board.insert(entry("low", 1, GameMode::Endless)), None);
+
+        assert_eq!(board.insert(entry("high", 100, GameMode::Endless)), Some(0));
+        let scores: Vec<u32> = board.entries(GameMode::Endless).map(|entry| entry.score).collect();
+        assert_eq!(scores.len(), SIZE);
+        assert_eq!(scores.last(), Some(&2));
+        assert_eq!(board.entries(GameMode::Zen).count(), SIZE);
+    }
+}
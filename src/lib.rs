@@ -0,0 +1,10 @@
+pub mod world;
+pub mod replay;
+pub mod snapshot;
+pub mod systems;
+pub mod grid;
+pub mod tuning;
+pub mod daily;
+pub mod achievements;
+pub mod stats;
+pub mod leaderboard;
@@ -1,25 +1,43 @@
-mod world;
 mod game;
 mod menu;
 mod gameover;
+mod profiles;
+mod versioned;
+mod controls;
 
+use std::collections::BTreeMap;
 use vek::*;
-use world::{Pos, Ori};
+use seal_the_sub::{
+    world::{self, Pos, Ori, GameMode},
+    replay,
+    snapshot,
+    systems,
+    tuning,
+    daily,
+    achievements::{self, Unlocked},
+    stats::{self, Stats},
+    leaderboard::{self, Leaderboard},
+};
 use specs::prelude::*;
 use quicksilver::{
     geom::Vector,
-    saving::{save, load},
-    lifecycle::{run, Settings, Window},
+    input::ButtonState,
+    lifecycle::{run, Settings, Window, Event},
 };
 use serde::{Serialize, Deserialize};
+use serde_json::Value;
 use crate::{
     game::Game,
     menu::Menu,
     gameover::GameOver,
+    profiles::Profiles,
+    versioned::{save_versioned, load_versioned, Migration, Device},
+    controls::Controls,
 };
 
 pub enum State {
     Game(Game),
+    Replay(Game),
     Menu(Menu),
     GameOver(GameOver),
 }
@@ -27,33 +45,165 @@ pub enum State {
 struct Engine {
     state: State,
     universals: Universals,
+    profiles: Profiles,
+}
+
+// Saved per profile. New fields with a #[serde(default)] don't need anything else, but renaming, removing or
+// restructuring one needs a migration added to the end of this list.
+const MIGRATIONS: &[Migration] = &[
+    high_scores_by_mode,
+];
+
+// Version 1 keeps the high scores for every mode together, rather than a field for each
+fn high_scores_by_mode(mut data: Value) -> Option<Value> {
+    let universals = data.as_object_mut()?;
+    let high_scores = [("Endless", "high_score"), ("TimeAttack", "time_attack_high_score"), ("Zen", "zen_high_score")]
+        .iter()
+        .filter_map(|(mode, field)| Some((mode.to_string(), universals.remove(*field)?)))
+        .collect();
+    universals.insert("high_scores".to_string(), Value::Object(high_scores));
+    Some(data)
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Universals {
-    high_score: u32,
+    #[serde(default)]
+    high_scores: BTreeMap<GameMode, u32>,
     total_score: u32,
+    // The last mode picked in the menu
+    #[serde(default)]
+    mode: GameMode,
+    // The best daily challenge score for each day played
+    #[serde(default)]
+    daily_bests: BTreeMap<i64, u32>,
+    #[serde(default)]
+    achievements: Unlocked,
+    // Over every run ever played
+    #[serde(default)]
+    stats: Stats,
+    #[serde(default)]
+    leaderboard: Leaderboard,
+    // The last name put on the leaderboard, offered again next time
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    controls: Controls,
+    // The id of the profile these belong to
+    #[serde(skip)]
+    profile: u32,
+    // Set when the saves couldn't be read or backed up, so that they don't get saved over
+    #[serde(skip)]
+    locked: bool,
+    // Set when the last save failed, so the menu can warn that progress isn't being kept
+    #[serde(skip)]
+    unsaved: bool,
+}
+
+impl Universals {
+    pub fn load(profile: u32) -> Self {
+        match load_versioned(&mut Device, &profiles::key(profiles::UNIVERSALS, profile), MIGRATIONS) {
+            Ok(universals) => Self { profile, ..universals.unwrap_or_default() },
+            Err(_) => Self { profile, locked: true, ..Self::default() },
+        }
+    }
+
+    pub fn save(&mut self) {
+        if !self.locked {
+            self.unsaved = save_versioned(&mut Device, &profiles::key(profiles::UNIVERSALS, self.profile), MIGRATIONS, self).is_err();
+        }
+    }
+
+    pub fn save_error(&self) -> Option<&'static str> {
+        if self.locked {
+            Some("Saves couldn't be read or backed up, so nothing will be saved")
+        } else if self.unsaved {
+            Some("Couldn't save, so progress may be lost")
+        } else {
+            None
+        }
+    }
+
+    pub fn high_score(&mut self, mode: GameMode) -> &mut u32 {
+        self.high_scores.entry(mode).or_insert(0)
+    }
+}
+
+impl Default for Universals {
+    fn default() -> Self {
+        Self {
+            high_scores: BTreeMap::new(),
+            total_score: 0,
+            mode: GameMode::Endless,
+            daily_bests: BTreeMap::new(),
+            achievements: Unlocked::default(),
+            stats: Stats::default(),
+            leaderboard: Leaderboard::default(),
+            name: String::new(),
+            controls: Controls::default(),
+            profile: 0,
+            locked: false,
+            unsaved: false,
+        }
+    }
 }
 
 impl quicksilver::lifecycle::State for Engine {
     fn new() -> quicksilver::Result<Self> {
+        let profiles = Profiles::load();
+        let universals = Universals::load(profiles.current().id);
         Ok(Self {
-            state: State::Menu(Menu::new()),
-            universals: load("seal-the-sub", "foo").unwrap_or(Universals {
-                high_score: 0,
-                total_score: 0,
-            })
+            state: State::Menu(Menu::new(universals.profile)),
+            universals,
+            profiles,
         })
     }
 
+    fn update(&mut self, window: &mut Window) -> quicksilver::Result<()> {
+        if let State::Game(game) | State::Replay(game) = &mut self.state {
+            if let Some(new_state) = game.update(window, &mut self.universals) {
+                self.state = new_state;
+                self.universals.save();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn event(&mut self, event: &Event, window: &mut Window) -> quicksilver::Result<()> {
+        // Single presses have to be caught here, since by the time anything gets drawn quicksilver has usually already
+        // turned them into holds. Key repeats come through as presses too, but the keyboard knows better.
+        let pressed = match event {
+            Event::Key(key, ButtonState::Pressed) if window.keyboard()[*key] == ButtonState::Pressed => Some(*key),
+            _ => None,
+        };
+
+        match &mut self.state {
+            State::Menu(menu) => {
+                menu.event(event);
+                if let Some(key) = pressed {
+                    menu.press(key, &mut self.universals, &mut self.profiles);
+                }
+            },
+            State::GameOver(gameover) => {
+                gameover.event(event);
+                if let Some(key) = pressed {
+                    gameover.press(key, &mut self.universals);
+                }
+            },
+            _ => {},
+        }
+
+        Ok(())
+    }
+
     fn draw(&mut self, window: &mut Window) -> quicksilver::Result<()> {
         if let Some(new_state) = match &mut self.state {
-            State::Game(game) => game.tick(window, &mut self.universals),
-            State::Menu(menu) => menu.tick(window, &mut self.universals),
+            State::Game(game) | State::Replay(game) => { game.draw(window, &self.universals.controls); None },
+            State::Menu(menu) => menu.tick(window, &mut self.universals, &mut self.profiles),
             State::GameOver(gameover) => gameover.tick(window, &mut self.universals),
         } {
             self.state = new_state;
-            save("seal-the-sub", "foo", &self.universals);
+            self.universals.save();
         }
 
         Ok(())
@@ -64,6 +214,33 @@ fn main() {
     run::<Engine>(
         "Seal the Sub",
         Vector::new(1000.0, 500.0),
-        Settings::default(),
+        Settings {
+            update_rate: 1000.0 / world::TICKS_PER_SECOND as f64,
+            // Don't try to catch up on more than a few frames' worth of simulation at once
+            max_updates: 8,
+            ..Settings::default()
+        },
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::versioned::{Memory, Storage};
+
+    #[test]
+    fn migrate_separate_high_scores() {
+        let mut storage = Memory::default();
+        storage.save("profile-0", &serde_json::json!({
+            "high_score": 12,
+            "time_attack_high_score": 34,
+            "total_score": 50,
+        })).unwrap();
+
+        let mut universals: Universals = load_versioned(&mut storage, "profile-0", MIGRATIONS).unwrap().unwrap();
+        assert_eq!(*universals.high_score(GameMode::Endless), 12);
+        assert_eq!(*universals.high_score(GameMode::TimeAttack), 34);
+        assert_eq!(*universals.high_score(GameMode::Zen), 0);
+        assert_eq!(universals.total_score, 50);
+    }
+}
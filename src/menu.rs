@@ -2,7 +2,7 @@ use vek::*;
 use specs::prelude::*;
 use quicksilver::{
     geom::{Rectangle, Triangle, Vector, Transform},
-    input::{Key, ButtonState},
+    input::Key,
     graphics::{Color, Background, Image, Font, FontStyle},
     sound::Sound,
     lifecycle::{Window, Event, Asset},
@@ -11,13 +11,40 @@ use rand::{thread_rng, prelude::*};
 use crate::{
     State,
     Universals,
-    world::{self, Pos, Ori, Vel, Body, Seafloor, Attr},
-    game::Game,
+    world::{self, Pos, Ori, Vel, Body, Seafloor, Attr, GameMode},
+    game::{Game, SavedRun},
+    daily,
+    achievements::Achievement,
+    leaderboard,
+    tuning::Tuning,
     menu,
+    gameover::type_name,
+    profiles::{Profiles, MAX_PROFILES},
+    controls::{Action, KEYS},
 };
 
+// Pages other than the main one take over the whole menu until they're closed
+enum Page {
+    Main,
+    Achievements,
+    Stats,
+    Leaderboard,
+    Profiles,
+    Controls,
+}
+
+// A profile name being typed in
+struct Naming {
+    // None for a new profile
+    id: Option<u32>,
+    name: String,
+    // Until the next frame, so that the letter that started naming doesn't get typed into the name
+    opening: bool,
+}
+
 pub struct Menu {
     time: f32,
+    page: Page,
 
     background: Asset<Image>,
     submarine: Asset<Image>,
@@ -30,14 +57,28 @@ pub struct Menu {
     music: Asset<Sound>,
 
     font: Asset<Font>,
+    tuning: Asset<Result<Tuning, String>>,
     music_playing: bool,
+    saved_run: Option<SavedRun>,
+
+    // The mode shown on the leaderboard page
+    board: GameMode,
+
+    // On the profile and controls pages
+    selected: usize,
+    naming: Option<Naming>,
+    deleting: bool,
+    // Waiting for a key to bind to this
+    binding: Option<Action>,
+    // The last key pressed couldn't be bound
+    clash: bool,
 }
 
 impl Menu {
-    pub fn new() -> Self {
-        let (globals, world) = world::create();
+    pub fn new(profile: u32) -> Self {
         Self {
             time: 0.0,
+            page: Page::Main,
             background: Asset::new(Image::load("splash.png")),
             submarine: Asset::new(Image::load("submarine.png")),
             seal: Asset::new(Image::load("seal.png")),
@@ -56,11 +97,63 @@ impl Menu {
             music: Asset::new(Sound::load("music.ogg")),
 
             font: Asset::new(Font::load("font.ttf")),
+            tuning: Game::load_tuning(),
             music_playing: false,
+            saved_run: Game::saved_run(profile),
+
+            board: GameMode::Endless,
+
+            selected: 0,
+            naming: None,
+            deleting: false,
+            binding: None,
+            clash: false,
+        }
+    }
+
+    pub fn event(&mut self, event: &Event) {
+        if let Some(naming) = self.naming.as_mut().filter(|naming| !naming.opening) {
+            type_name(&mut naming.name, event);
+        }
+    }
+
+    pub fn press(&mut self, key: Key, universals: &mut Universals, profiles: &mut Profiles) {
+        match self.page {
+            Page::Main => match key {
+                Key::A => self.page = Page::Achievements,
+                Key::K => {
+                    self.page = Page::Controls;
+                    self.selected = 0;
+                },
+                Key::P => {
+                    self.page = Page::Profiles;
+                    self.selected = profiles.list().iter().position(|profile| profile.id == universals.profile).unwrap_or(0);
+                },
+                Key::S => self.page = Page::Stats,
+                Key::L => {
+                    self.page = Page::Leaderboard;
+                    self.board = if leaderboard::MODES.contains(&universals.mode) { universals.mode } else { leaderboard::MODES[0] };
+                },
+                Key::Left | Key::Right => universals.mode = Self::cycle_mode(&GameMode::ALL, universals.mode, key),
+                _ => {},
+            },
+            Page::Achievements => if let Key::A | Key::Escape = key {
+                self.page = Page::Main;
+            },
+            Page::Stats => if let Key::S | Key::Escape = key {
+                self.page = Page::Main;
+            },
+            Page::Profiles => self.press_profiles(key, universals, profiles),
+            Page::Controls => self.press_controls(key, universals),
+            Page::Leaderboard => match key {
+                Key::L | Key::Escape => self.page = Page::Main,
+                Key::Left | Key::Right => self.board = Self::cycle_mode(&leaderboard::MODES, self.board, key),
+                _ => {},
+            },
         }
     }
 
-    pub fn tick(&mut self, window: &mut Window, universals: &mut Universals) -> Option<State> {
+    pub fn tick(&mut self, window: &mut Window, universals: &mut Universals, profiles: &mut Profiles) -> Option<State> {
         let time = self.time;
 
         let mut music_playing = &mut self.music_playing;
@@ -72,9 +165,38 @@ impl Menu {
             }).unwrap();
         }
 
+        match self.page {
+            Page::Main => {},
+            Page::Achievements => return self.tick_achievements(window, universals),
+            Page::Stats => return self.tick_stats(window, universals),
+            Page::Leaderboard => return self.tick_leaderboard(window, universals),
+            Page::Profiles => return self.tick_profiles(window, universals, profiles),
+            Page::Controls => return self.tick_controls(window, universals),
+        }
+
         // Handle input
-        if window.keyboard()[Key::Space].is_down() && time > 0.5 {
-            return Some(State::Game(Game::new()))
+        if universals.controls.is_down(window, Action::Confirm) && time > 0.5 {
+            if let Some(Ok(tuning)) = Game::loaded_tuning(&mut self.tuning) {
+                return Some(State::Game(Game::new(None, tuning, universals.mode)))
+            }
+        }
+
+        if window.keyboard()[Key::D].is_down() && time > 0.5 {
+            if let Some(Ok(tuning)) = Game::loaded_tuning(&mut self.tuning) {
+                return Some(State::Game(Game::daily(daily::today(), tuning)))
+            }
+        }
+
+        if window.keyboard()[Key::C].is_down() && time > 0.5 {
+            if let Some(game) = self.saved_run.take().and_then(Game::resume) {
+                return Some(State::Game(game))
+            }
+        }
+
+        if window.keyboard()[Key::R].is_down() && time > 0.5 {
+            if let Some(game) = Game::last_replay(universals.profile).and_then(|replay| Game::replay(&replay, &mut self.tuning)) {
+                return Some(State::Replay(game))
+            }
         }
 
         window.clear(Color::from_rgba(120, 200, 255, 1.0));
@@ -104,7 +226,48 @@ impl Menu {
                         Ok(())
                     });
 
+        let has_saved_run = self.saved_run.is_some();
+        let mode = universals.mode;
+        let high_score = *universals.high_score(mode);
+        let today = daily::today();
+        let daily_best = universals.daily_bests.get(&today).copied();
+        let unlocked = universals.achievements.len();
+        let profile = &profiles.current().name;
+        let confirm = universals.controls.key_name(Action::Confirm);
+        // New runs can't start until it's fixed
+        let tuning_error = match Game::loaded_tuning(&mut self.tuning) {
+            Some(Err(err)) => Some(format!("Can't play, tuning.json is broken: {}", err)),
+            _ => None,
+        };
+        let save_error = universals.save_error().or_else(|| profiles.save_error());
         self.font.execute(|font| {
+            if let Some(err) = &tuning_error {
+                let img = font.render(err, &FontStyle::new(20.0, Color::RED)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((120.0, 20.0)),
+                    16.0,
+                );
+            }
+            if let Some(err) = save_error {
+                let img = font.render(err, &FontStyle::new(20.0, Color::RED)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((120.0, 40.0)),
+                    16.0,
+                );
+            }
+
+            let img = font.render(&format!("Playing as {} (P to change, K for controls)", profile), &FontStyle::new(32.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 60.0)),
+                10.0,
+            );
+
             let img = font.render("Seal the Sub", &FontStyle::new(64.0, Color::WHITE)).unwrap();
             window.draw_ex(
                 &img.area(),
@@ -113,7 +276,15 @@ impl Menu {
                 10.0,
             );
 
-            let img = font.render(&format!("High Score: {}", universals.high_score), &FontStyle::new(48.0, Color::WHITE)).unwrap();
+            let img = font.render(&format!("< {} >  (LEFT/RIGHT to change mode)", mode.name()), &FontStyle::new(32.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 190.0)),
+                10.0,
+            );
+
+            let img = font.render(&format!("High Score: {}", high_score), &FontStyle::new(48.0, Color::WHITE)).unwrap();
             window.draw_ex(
                 &img.area(),
                 Background::Img(&img),
@@ -121,22 +292,41 @@ impl Menu {
                 10.0,
             );
 
-            let img = font.render(&format!("Total Score: {}", universals.total_score), &FontStyle::new(48.0, Color::WHITE)).unwrap();
+            let img = font.render(&format!("Total Score: {}", universals.total_score), &FontStyle::new(32.0, Color::WHITE)).unwrap();
             window.draw_ex(
                 &img.area(),
                 Background::Img(&img),
-                Transform::translate((120.0, 300.0)),
+                Transform::translate((120.0, 292.0)),
                 10.0,
             );
 
-            let img = font.render("Press SPACE to play", &FontStyle::new(48.0, Color::WHITE)).unwrap();
+            let img = font.render(&format!("Press {} to play", confirm), &FontStyle::new(48.0, Color::WHITE)).unwrap();
             window.draw_ex(
                 &img.area(),
                 Background::Img(&img),
-                Transform::translate((120.0, 380.0)),
+                Transform::translate((120.0, 340.0)),
                 10.0,
             );
 
+            let daily_hint = match daily_best {
+                Some(best) => format!("Press D for the {} challenge (best: {})", daily::date(today), best),
+                None => format!("Press D for the {} challenge", daily::date(today)),
+            };
+            let hints = Some(daily_hint)
+                .into_iter()
+                .chain(if has_saved_run { Some("Press C to continue your run".to_string()) } else { None })
+                .chain(Some("Press R to watch the last replay".to_string()))
+                .chain(Some(format!("Press A for achievements ({}/{}), S for stats, L for the leaderboard", unlocked, Achievement::ALL.len())));
+            for (i, hint) in hints.enumerate() {
+                let img = font.render(&hint, &FontStyle::new(24.0, Color::WHITE)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((120.0, 400.0 + i as f32 * 22.0)),
+                    10.0,
+                );
+            }
+
             Ok(())
         });
 
@@ -144,5 +334,416 @@ impl Menu {
 
         None
     }
-}
 
+    // Cycle through the game modes, backwards for left
+    fn cycle_mode(modes: &[GameMode], mode: GameMode, key: Key) -> GameMode {
+        let mode_idx = modes.iter().position(|other| *other == mode).unwrap_or(0);
+        match key {
+            Key::Left => modes[(mode_idx + modes.len() - 1) % modes.len()],
+            _ => modes[(mode_idx + 1) % modes.len()],
+        }
+    }
+
+    fn tick_achievements(&mut self, window: &mut Window, universals: &mut Universals) -> Option<State> {
+        window.clear(Color::from_rgba(120, 200, 255, 1.0));
+
+        self.font.execute(|font| {
+            let img = font.render("Achievements", &FontStyle::new(64.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 40.0)),
+                10.0,
+            );
+
+            for (i, achievement) in Achievement::ALL.iter().enumerate() {
+                let (mark, colour) = if universals.achievements.contains(*achievement) {
+                    ("[x]", Color::WHITE)
+                } else {
+                    ("[ ]", Color::from_rgba(40, 80, 120, 1.0))
+                };
+                let text = format!("{} {} - {}", mark, achievement.name(), achievement.description());
+                let img = font.render(&text, &FontStyle::new(32.0, colour)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((120.0, 130.0 + i as f32 * 44.0)),
+                    10.0,
+                );
+            }
+
+            let img = font.render("Press A or ESCAPE to go back", &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 452.0)),
+                10.0,
+            );
+
+            Ok(())
+        });
+
+        self.time += 1.0 / 60.0;
+
+        None
+    }
+
+    fn tick_stats(&mut self, window: &mut Window, universals: &mut Universals) -> Option<State> {
+        window.clear(Color::from_rgba(120, 200, 255, 1.0));
+
+        let stats = &universals.stats;
+        let seconds = stats.seconds() as u64;
+        let lines = vec![
+            format!("Runs played: {}", stats.runs),
+            format!("Time played: {}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60),
+            format!("Fish eaten: {}", stats.fish_eaten),
+            format!("Fuel delivered: {}", stats.fuel_delivered),
+            format!("Tape delivered: {}", stats.tape_delivered),
+            format!("Deepest dive: {}", stats.deepest_dive.floor()),
+            format!("Highest jump: {}", stats.highest_jump.floor()),
+        ]
+            .into_iter()
+            .chain(stats.deaths.iter().map(|(loss, n)| format!("{}: {}", loss.describe(), n)))
+            .collect::<Vec<_>>();
+
+        self.font.execute(|font| {
+            let img = font.render("Stats", &FontStyle::new(64.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 40.0)),
+                10.0,
+            );
+
+            // Two columns, so that there's room for every cause of death
+            for (i, line) in lines.iter().enumerate() {
+                let img = font.render(line, &FontStyle::new(28.0, Color::WHITE)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((60.0 + (i / 7) as f32 * 440.0, 130.0 + (i % 7) as f32 * 40.0)),
+                    10.0,
+                );
+            }
+
+            let img = font.render("Press S or ESCAPE to go back", &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 452.0)),
+                10.0,
+            );
+
+            Ok(())
+        });
+
+        self.time += 1.0 / 60.0;
+
+        None
+    }
+
+    fn tick_leaderboard(&mut self, window: &mut Window, universals: &mut Universals) -> Option<State> {
+        window.clear(Color::from_rgba(120, 200, 255, 1.0));
+
+        let mode = self.board;
+        let rows = universals.leaderboard
+            .entries(mode)
+            .enumerate()
+            .map(|(i, entry)| {
+                let seconds = entry.seconds as u32;
+                vec![
+                    format!("{}.", i + 1),
+                    entry.name.clone(),
+                    entry.score.to_string(),
+                    daily::date(entry.day),
+                    format!("{}:{:02}", seconds / 60, seconds % 60),
+                    entry.loss.describe().to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        self.font.execute(|font| {
+            let img = font.render("Leaderboard", &FontStyle::new(64.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 40.0)),
+                10.0,
+            );
+
+            let img = font.render(&format!("< {} >  (LEFT/RIGHT to change mode)", mode.name()), &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 95.0)),
+                10.0,
+            );
+
+            if rows.is_empty() {
+                let img = font.render("No runs yet", &FontStyle::new(32.0, Color::WHITE)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((120.0, 160.0)),
+                    10.0,
+                );
+            }
+
+            // Rank, name, score, date, duration and what ended the run, each in its own column
+            const COLUMNS: [f32; 6] = [60.0, 100.0, 280.0, 380.0, 500.0, 570.0];
+            for (i, row) in rows.iter().enumerate() {
+                for (cell, x) in row.iter().zip(COLUMNS.iter()) {
+                    let img = font.render(cell, &FontStyle::new(16.0, Color::WHITE)).unwrap();
+                    window.draw_ex(
+                        &img.area(),
+                        Background::Img(&img),
+                        Transform::translate((*x, 128.0 + i as f32 * 15.5)),
+                        10.0,
+                    );
+                }
+            }
+
+            let img = font.render("Press L or ESCAPE to go back", &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 452.0)),
+                10.0,
+            );
+
+            Ok(())
+        });
+
+        self.time += 1.0 / 60.0;
+
+        None
+    }
+
+    fn press_profiles(&mut self, key: Key, universals: &mut Universals, profiles: &mut Profiles) {
+        let count = profiles.list().len();
+        self.selected = self.selected.min(count - 1);
+        let selected = profiles.list()[self.selected].id;
+
+        if let Some(naming) = &mut self.naming {
+            if key == Key::Back {
+                naming.name.pop();
+            }
+
+            if key == Key::Return && !naming.name.trim().is_empty() {
+                let name = naming.name.trim().to_string();
+                match naming.id {
+                    Some(id) => profiles.rename(id, name),
+                    None => if let Some(id) = profiles.create(name.clone()) {
+                        universals.save();
+                        *universals = Universals::load(id);
+                        universals.name = name;
+                        profiles.select(id);
+                        self.selected = count;
+                        self.saved_run = None;
+                    },
+                }
+                profiles.save();
+                self.naming = None;
+            } else if key == Key::Escape {
+                self.naming = None;
+            }
+        } else if key == Key::X {
+            // Deleting takes a second press to confirm
+            if self.deleting && profiles.delete(selected) {
+                if selected == universals.profile {
+                    *universals = Universals::load(profiles.current().id);
+                    self.saved_run = Game::saved_run(universals.profile);
+                }
+                profiles.save();
+                self.deleting = false;
+            } else {
+                self.deleting = count > 1;
+            }
+        } else if key == Key::P || key == Key::Escape {
+            self.page = Page::Main;
+            self.deleting = false;
+        } else if key == Key::Up {
+            self.selected = (self.selected + count - 1) % count;
+            self.deleting = false;
+        } else if key == Key::Down {
+            self.selected = (self.selected + 1) % count;
+            self.deleting = false;
+        } else if key == Key::Return && selected != universals.profile {
+            universals.save();
+            *universals = Universals::load(selected);
+            profiles.select(selected);
+            profiles.save();
+            self.saved_run = Game::saved_run(selected);
+        } else if key == Key::N && count < MAX_PROFILES {
+            self.naming = Some(Naming { id: None, name: String::new(), opening: true });
+            self.deleting = false;
+        } else if key == Key::E {
+            self.naming = Some(Naming { id: Some(selected), name: profiles.list()[self.selected].name.clone(), opening: true });
+            self.deleting = false;
+        }
+    }
+
+    fn tick_profiles(&mut self, window: &mut Window, universals: &mut Universals, profiles: &mut Profiles) -> Option<State> {
+        self.selected = self.selected.min(profiles.list().len() - 1);
+        if let Some(naming) = &mut self.naming {
+            naming.opening = false;
+        }
+
+        window.clear(Color::from_rgba(120, 200, 255, 1.0));
+
+        let time = self.time;
+        let current = universals.profile;
+        let selected = self.selected;
+        let naming = &self.naming;
+        let deleting = self.deleting;
+        self.font.execute(|font| {
+            let img = font.render("Profiles", &FontStyle::new(64.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 40.0)),
+                10.0,
+            );
+
+            // A new profile's name goes on an extra row at the bottom
+            let cursor = if time % 1.0 < 0.5 { "_" } else { "" };
+            let rows = profiles.list()
+                .iter()
+                .map(|profile| match naming {
+                    Some(Naming { id: Some(id), name, .. }) if *id == profile.id => (profile.id, format!("{}{}", name, cursor)),
+                    _ => (profile.id, profile.name.clone()),
+                })
+                .chain(match naming {
+                    Some(Naming { id: None, name, .. }) => Some((u32::MAX, format!("{}{}", name, cursor))),
+                    _ => None,
+                });
+            for (i, (id, name)) in rows.enumerate() {
+                let text = format!(
+                    "{} {}{}",
+                    if i == selected && naming.is_none() { ">" } else { " " },
+                    name,
+                    if id == current { " (playing)" } else { "" },
+                );
+                let img = font.render(&text, &FontStyle::new(32.0, Color::WHITE)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((120.0, 130.0 + i as f32 * 40.0)),
+                    10.0,
+                );
+            }
+
+            let hint = if naming.is_some() {
+                "Type a name, then press ENTER to save or ESCAPE to cancel"
+            } else if deleting {
+                "Press X again to delete this profile and all of its progress"
+            } else {
+                "UP/DOWN to choose, ENTER to play as, N for new, E to rename, X to delete"
+            };
+            let img = font.render(hint, &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 426.0)),
+                10.0,
+            );
+
+            let img = font.render("Press P or ESCAPE to go back", &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 452.0)),
+                10.0,
+            );
+
+            Ok(())
+        });
+
+        self.time += 1.0 / 60.0;
+
+        None
+    }
+
+    fn press_controls(&mut self, key: Key, universals: &mut Universals) {
+        let count = Action::ALL.len();
+        if let Some(action) = self.binding {
+            // Any key will do, including the ones that normally leave this page
+            if KEYS.contains(&key) {
+                self.clash = !universals.controls.bind(action, key);
+                if !self.clash {
+                    universals.save();
+                    self.binding = None;
+                }
+            }
+        } else if key == Key::K || key == Key::Escape {
+            self.page = Page::Main;
+        } else if key == Key::Up {
+            self.selected = (self.selected + count - 1) % count;
+        } else if key == Key::Down {
+            self.selected = (self.selected + 1) % count;
+        } else if key == Key::Return {
+            self.binding = Some(Action::ALL[self.selected % count]);
+            self.clash = false;
+        } else if key == Key::Back {
+            universals.controls.reset();
+            universals.save();
+        }
+    }
+
+    fn tick_controls(&mut self, window: &mut Window, universals: &mut Universals) -> Option<State> {
+        window.clear(Color::from_rgba(120, 200, 255, 1.0));
+
+        let selected = self.selected;
+        let binding = self.binding;
+        let clash = self.clash;
+        let controls = &universals.controls;
+        self.font.execute(|font| {
+            let img = font.render("Controls", &FontStyle::new(64.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 40.0)),
+                10.0,
+            );
+
+            for (i, action) in Action::ALL.iter().enumerate() {
+                let key = if binding == Some(*action) { "...".to_string() } else { controls.key_name(*action) };
+                let text = format!("{} {}: {}", if i == selected { ">" } else { " " }, action.name(), key);
+                let img = font.render(&text, &FontStyle::new(32.0, Color::WHITE)).unwrap();
+                window.draw_ex(
+                    &img.area(),
+                    Background::Img(&img),
+                    Transform::translate((120.0, 130.0 + i as f32 * 44.0)),
+                    10.0,
+                );
+            }
+
+            let hint = match binding {
+                Some(_) if clash => "Confirm can't use a key the menus need, so try another".to_string(),
+                Some(action) => format!("Press the new key for {}", action.name().to_lowercase()),
+                None => "UP/DOWN to choose, ENTER to change, BACKSPACE to reset them all".to_string(),
+            };
+            let img = font.render(&hint, &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 426.0)),
+                10.0,
+            );
+
+            let img = font.render("Press K or ESCAPE to go back", &FontStyle::new(24.0, Color::WHITE)).unwrap();
+            window.draw_ex(
+                &img.area(),
+                Background::Img(&img),
+                Transform::translate((120.0, 452.0)),
+                10.0,
+            );
+
+            Ok(())
+        });
+
+        self.time += 1.0 / 60.0;
+
+        None
+    }
+}
@@ -0,0 +1,258 @@
+use quicksilver::saving::SaveError;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::versioned::{save_versioned, load_versioned, Migration, Storage, Device};
+
+const MIGRATIONS: &[Migration] = &[];
+
+// Everything a profile saves goes under one of these, suffixed with the profile's id
+pub const UNIVERSALS: &str = "profile";
+pub const RUN: &str = "run";
+pub const REPLAY: &str = "replay";
+
+// As many as fit on the profile page
+pub const MAX_PROFILES: usize = 7;
+
+// Profiles can be made and deleted without saving anything, so a few ids in a row without saves doesn't mean there are
+// none after them
+const PROBE_GAP: u32 = 64;
+
+pub fn key(what: &str, id: u32) -> String {
+    format!("{}-{}", what, id)
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Profile {
+    // Names can change, so saves are keyed by this instead
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Profiles {
+    list: Vec<Profile>,
+    current: u32,
+    next_id: u32,
+    // Set when the saved list couldn't be read or backed up, so that it doesn't get saved over
+    #[serde(skip)]
+    locked: bool,
+    // Set when the last save failed
+    #[serde(skip)]
+    unsaved: bool,
+}
+
+impl Profiles {
+    pub fn load() -> Self {
+        match load_versioned(&mut Device, "profiles", MIGRATIONS) {
+            Ok(Some(profiles)) => profiles,
+            Ok(None) => {
+                let mut profiles = Self::recover(&mut Device);
+                profiles.save();
+                profiles
+            },
+            Err(_) => Self { locked: true, ..Self::recover(&mut Device) },
+        }
+    }
+
+    pub fn save(&mut self) {
+        if !self.locked {
+            self.unsaved = save_versioned(&mut Device, "profiles", MIGRATIONS, self).is_err();
+        }
+    }
+
+    pub fn save_error(&self) -> Option<&'static str> {
+        if self.locked {
+            Some("The profile list couldn't be read or backed up, so it won't be saved")
+        } else if self.unsaved {
+            Some("Couldn't save the profile list")
+        } else {
+            None
+        }
+    }
+
+    // Rebuilds the list from whatever profiles have saves, for when it's missing. Before profiles there was a single
+    // set of saves, which becomes the first profile unless that already has saves of its own.
+    fn recover(storage: &mut impl Storage) -> Self {
+        let exists = storage.exists(&key(UNIVERSALS, 0));
+        if let (false, Ok(universals)) = (exists, storage.load("foo")) {
+            storage.save(&key(UNIVERSALS, 0), &universals);
+        }
+        if let (false, Ok(run)) = (exists, storage.load("run")) {
+            storage.save(&key(RUN, 0), &run);
+        }
+        if let (false, Ok(replay)) = (exists, storage.load_raw("replay")) {
+            storage.save_raw(&key(REPLAY, 0), &replay);
+        }
+
+        // Ids are never reused, so every id with saves has to stay taken, even the deleted ones
+        let mut list = Vec::new();
+        let mut next_id = 0;
+        let mut id = 0;
+        while id < next_id + PROBE_GAP {
+            let universals = key(UNIVERSALS, id);
+            if [&universals, &key(RUN, id), &key(REPLAY, id)].iter().any(|key| storage.exists(key)) {
+                next_id = id + 1;
+            }
+
+            // Deleted profiles have their saves replaced with null
+            let saved = storage.load(&universals).ok();
+            if storage.exists(&universals) && saved != Some(Value::Null) {
+                let name = saved
+                    .as_ref()
+                    .and_then(|saved| saved.pointer("/data/name").or_else(|| saved.get("name")))
+                    .and_then(Value::as_str)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string);
+                let name = name.unwrap_or_else(|| match list.len() {
+                    0 => "Player".to_string(),
+                    n => format!("Player {}", n + 1),
+                });
+                list.push(Profile { id, name });
+            }
+            id += 1;
+        }
+
+        if list.is_empty() {
+            list.push(Profile { id: next_id, name: "Player".to_string() });
+            next_id += 1;
+        }
+        Self {
+            current: list[0].id,
+            list,
+            next_id,
+            locked: false,
+            unsaved: false,
+        }
+    }
+
+    pub fn list(&self) -> &[Profile] {
+        &self.list
+    }
+
+    pub fn current(&self) -> &Profile {
+        self.list
+            .iter()
+            .find(|profile| profile.id == self.current)
+            .unwrap_or(&self.list[0])
+    }
+
+    pub fn select(&mut self, id: u32) {
+        if self.list.iter().any(|profile| profile.id == id) {
+            self.current = id;
+        }
+    }
+
+    pub fn create(&mut self, name: String) -> Option<u32> {
+        if self.list.len() >= MAX_PROFILES {
+            return None;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.list.push(Profile { id, name });
+        Some(id)
+    }
+
+    pub fn rename(&mut self, id: u32, name: String) {
+        if let Some(profile) = self.list.iter_mut().find(|profile| profile.id == id) {
+            profile.name = name;
+        }
+    }
+
+    // The last profile can't be deleted, and deleting the current one switches to another
+    pub fn delete(&mut self, id: u32) -> bool {
+        self.delete_from(&mut Device, id).unwrap_or(false)
+    }
+
+    // Profiles whose saves couldn't be cleared are kept, or they'd come back the next time the list is recovered
+    fn delete_from(&mut self, storage: &mut impl Storage, id: u32) -> Result<bool, SaveError> {
+        if self.list.len() <= 1 || !self.list.iter().any(|profile| profile.id == id) {
+            return Ok(false);
+        }
+
+        // Saves can't be removed, only overwritten with something that won't load
+        storage.save(&key(UNIVERSALS, id), &Value::Null)?;
+        storage.save(&key(RUN, id), &Value::Null)?;
+        storage.save_raw(&key(REPLAY, id), &[])?;
+
+        self.list.retain(|profile| profile.id != id);
+        if self.current == id {
+            self.current = self.list[0].id;
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::versioned::Memory;
+
+    #[test]
+    fn recover_profiles_with_saves() {
+        let mut storage = Memory::default();
+        storage.save(&key(UNIVERSALS, 0), &json!({ "version": 1, "data": { "name": "Ann" } })).unwrap();
+        storage.save(&key(UNIVERSALS, 2), &Value::Null).unwrap();
+        storage.save(&key(UNIVERSALS, 3), &json!({ "version": 1, "data": {} })).unwrap();
+        storage.save(&key(RUN, 5), &Value::Null).unwrap();
+
+        let profiles = Profiles::recover(&mut storage);
+        let names: Vec<_> = profiles.list().iter().map(|profile| (profile.id, profile.name.as_str())).collect();
+        assert_eq!(names, [(0, "Ann"), (3, "Player 2")]);
+        assert_eq!(profiles.next_id, 6);
+    }
+
+    #[test]
+    fn legacy_saves_become_the_first_profile() {
+        let mut storage = Memory::default();
+        storage.save("foo", &json!({ "high_score": 5, "total_score": 5 })).unwrap();
+        storage.save_raw("replay", b"STSR").unwrap();
+
+        let profiles = Profiles::recover(&mut storage);
+        assert_eq!(profiles.list().len(), 1);
+        assert_eq!(profiles.current().id, 0);
+        assert_eq!(profiles.next_id, 1);
+        assert_eq!(storage.load(&key(UNIVERSALS, 0)).unwrap(), json!({ "high_score": 5, "total_score": 5 }));
+        assert_eq!(storage.load_raw(&key(REPLAY, 0)).unwrap(), b"STSR");
+    }
+
+    #[test]
+    fn nothing_saved() {
+        let profiles = Profiles::recover(&mut Memory::default());
+        assert_eq!(profiles.list().len(), 1);
+        assert_eq!(profiles.next_id, 1);
+    }
+
+    fn two_profiles(storage: &mut Memory) -> Profiles {
+        storage.save(&key(UNIVERSALS, 0), &json!({ "version": 1, "data": { "name": "Ann" } })).unwrap();
+        storage.save(&key(UNIVERSALS, 1), &json!({ "version": 1, "data": { "name": "Bob" } })).unwrap();
+        storage.save(&key(RUN, 1), &json!({ "version": 1, "data": {} })).unwrap();
+        storage.save_raw(&key(REPLAY, 1), b"STSR").unwrap();
+        Profiles::recover(storage)
+    }
+
+    #[test]
+    fn deleted_profiles_stay_deleted() {
+        let mut storage = Memory::default();
+        let mut profiles = two_profiles(&mut storage);
+        profiles.select(1);
+        assert!(profiles.delete_from(&mut storage, 1).unwrap());
+        assert_eq!(profiles.current().id, 0);
+        assert!(!profiles.delete_from(&mut storage, 0).unwrap());
+
+        let recovered = Profiles::recover(&mut storage);
+        let ids: Vec<_> = recovered.list().iter().map(|profile| profile.id).collect();
+        assert_eq!(ids, [0]);
+        assert_eq!(recovered.next_id, 2);
+    }
+
+    #[test]
+    fn failed_deletes_keep_the_profile() {
+        let mut storage = Memory::default();
+        let mut profiles = two_profiles(&mut storage);
+        storage.read_only = true;
+        assert!(profiles.delete_from(&mut storage, 1).is_err());
+        assert_eq!(profiles.list().len(), 2);
+    }
+}
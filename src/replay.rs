@@ -0,0 +1,297 @@
+use serde::{Serialize, Deserialize};
+use crate::{
+    world::{Inputs, GameMode},
+    tuning::Tuning,
+};
+
+const MAGIC: &[u8; 4] = b"STSR";
+// Version 1 had no game mode, so those replays were all endless. Versions 1 and 2 stored inputs as bits for left, right
+// and boost, before they were analog. Version 4 added the tuning the run was played with.
+const VERSION: u8 = 4;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    BadMagic,
+    BadVersion(u8),
+    BadMode(u8),
+    BadTuning,
+    Truncated,
+}
+
+// A seed, mode and tuning plus the inputs for every tick of a run, run-length encoded
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub mode: GameMode,
+    // None for replays from before they kept their tuning
+    #[serde(default)]
+    pub tuning: Option<Tuning>,
+    runs: Vec<([u8; 2], u32)>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, mode: GameMode, tuning: Tuning) -> Self {
+        Self {
+            seed,
+            mode,
+            tuning: Some(tuning),
+            runs: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, inputs: Inputs) {
+        let encoded = encode(inputs);
+        match self.runs.last_mut() {
+            Some((last, n)) if *last == encoded => *n += 1,
+            _ => self.runs.push((encoded, 1)),
+        }
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.runs.iter().map(|(_, n)| n).sum()
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item=Inputs> + '_ {
+        self.runs
+            .iter()
+            .flat_map(|(encoded, n)| (0..*n).map(move |_| decode(*encoded)))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(14 + self.runs.len() * 3);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.push(mode_to_byte(self.mode));
+        let tuning = self.tuning.as_ref().and_then(|tuning| serde_json::to_vec(tuning).ok()).unwrap_or_default();
+        write_varint(&mut bytes, tuning.len() as u32);
+        bytes.extend_from_slice(&tuning);
+        for (encoded, n) in self.runs.iter() {
+            bytes.extend_from_slice(encoded);
+            write_varint(&mut bytes, *n);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        if bytes.len() < 13 {
+            return Err(ReplayError::Truncated);
+        } else if &bytes[0..4] != MAGIC {
+            return Err(ReplayError::BadMagic);
+        } else if bytes[4] < 1 || bytes[4] > VERSION {
+            return Err(ReplayError::BadVersion(bytes[4]));
+        }
+
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&bytes[5..13]);
+
+        let (mode, mut rest) = match bytes[4] {
+            1 => (GameMode::Endless, &bytes[13..]),
+            _ => {
+                let (mode, rest) = bytes[13..].split_first().ok_or(ReplayError::Truncated)?;
+                (mode_from_byte(*mode).ok_or(ReplayError::BadMode(*mode))?, rest)
+            },
+        };
+
+        // Empty if the tuning wasn't known when it was written
+        let tuning = match bytes[4] {
+            1..=3 => None,
+            _ => {
+                let (len, tail) = read_varint(rest).ok_or(ReplayError::Truncated)?;
+                let len = len as usize;
+                if tail.len() < len {
+                    return Err(ReplayError::Truncated);
+                }
+                rest = &tail[len..];
+                match len {
+                    0 => None,
+                    _ => Some(Tuning::from_json(&tail[..len]).map_err(|_| ReplayError::BadTuning)?),
+                }
+            },
+        };
+
+        let mut runs = Vec::new();
+        while !rest.is_empty() {
+            let (encoded, tail) = match bytes[4] {
+                1 | 2 => (from_bits(rest[0]), &rest[1..]),
+                _ if rest.len() >= 2 => ([rest[0], rest[1]], &rest[2..]),
+                _ => return Err(ReplayError::Truncated),
+            };
+            let (n, tail) = read_varint(tail).ok_or(ReplayError::Truncated)?;
+            runs.push((encoded, n));
+            rest = tail;
+        }
+
+        Ok(Self {
+            seed: u64::from_le_bytes(seed),
+            mode,
+            tuning,
+            runs,
+        })
+    }
+}
+
+// Steering as a signed byte, then boost
+fn encode(inputs: Inputs) -> [u8; 2] {
+    [
+        (inputs.steer * Inputs::STEER_STEPS).round() as i8 as u8,
+        (inputs.boost * Inputs::BOOST_STEPS).round() as u8,
+    ]
+}
+
+fn decode(encoded: [u8; 2]) -> Inputs {
+    Inputs::new(encoded[0] as i8 as f32 / Inputs::STEER_STEPS, encoded[1] as f32 / Inputs::BOOST_STEPS)
+}
+
+// Left, right and boost bits from versions 1 and 2. Left and right together cancel out.
+fn from_bits(bits: u8) -> [u8; 2] {
+    let steer = (bits & 2 != 0) as i8 - (bits & 1 != 0) as i8;
+    encode(Inputs::new(steer as f32, (bits & 4 != 0) as u8 as f32))
+}
+
+fn mode_to_byte(mode: GameMode) -> u8 {
+    match mode {
+        GameMode::Endless => 0,
+        GameMode::TimeAttack => 1,
+        GameMode::Zen => 2,
+    }
+}
+
+fn mode_from_byte(byte: u8) -> Option<GameMode> {
+    match byte {
+        0 => Some(GameMode::Endless),
+        1 => Some(GameMode::TimeAttack),
+        2 => Some(GameMode::Zen),
+        _ => None,
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut n: u32) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn read_varint(bytes: &[u8]) -> Option<(u32, &[u8])> {
+    let mut n = 0u32;
+    for (i, byte) in bytes.iter().enumerate().take(5) {
+        n |= ((byte & 0x7F) as u32) << (i * 7);
+        if byte & 0x80 == 0 {
+            return Some((n, &bytes[i + 1..]));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(replay: &Replay) -> Vec<(f32, f32)> {
+        replay.inputs().map(|inputs| (inputs.steer, inputs.boost)).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut tuning = Tuning::default();
+        tuning.decay = 0.5;
+        let mut replay = Replay::new(0x0123_4567_89AB_CDEF, GameMode::TimeAttack, tuning);
+        for i in 0..300 {
+            replay.record(Inputs::new((i / 7) as f32 * 0.13 - 1.0, (i / 50) as f32 * 0.2));
+        }
+
+        let read = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(read.seed, replay.seed);
+        assert_eq!(read.mode, GameMode::TimeAttack);
+        assert_eq!(read.tuning.as_ref().map(|tuning| tuning.decay), Some(0.5));
+        assert_eq!(read.ticks(), 300);
+        assert_eq!(inputs(&read), inputs(&replay));
+    }
+
+    #[test]
+    fn round_trip_without_tuning() {
+        let mut replay = Replay::new(7, GameMode::Zen, Tuning::default());
+        replay.tuning = None;
+        replay.record(Inputs::new(0.5, 1.0));
+
+        let read = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert!(read.tuning.is_none());
+        assert_eq!(inputs(&read), [(Inputs::new(0.5, 0.0).steer, 1.0)]);
+    }
+
+    #[test]
+    fn version_1() {
+        let mut bytes = b"STSR\x01".to_vec();
+        bytes.extend_from_slice(&42u64.to_le_bytes());
+        // Left for 2 ticks, then right and boost for 1, then left and right together for 200
+        bytes.extend_from_slice(&[0b001, 2, 0b110, 1, 0b011, 0xC8, 0x01]);
+
+        let read = Replay::from_bytes(&bytes).unwrap();
+        assert_eq!(read.seed, 42);
+        assert_eq!(read.mode, GameMode::Endless);
+        assert!(read.tuning.is_none());
+        assert_eq!(read.ticks(), 203);
+        let read = inputs(&read);
+        assert_eq!(read[..3], [(-1.0, 0.0), (-1.0, 0.0), (1.0, 1.0)]);
+        assert!(read[3..].iter().all(|inputs| *inputs == (0.0, 0.0)));
+    }
+
+    #[test]
+    fn version_2() {
+        let mut bytes = b"STSR\x02".to_vec();
+        bytes.extend_from_slice(&42u64.to_le_bytes());
+        bytes.extend_from_slice(&[2, 0b100, 3]);
+
+        let read = Replay::from_bytes(&bytes).unwrap();
+        assert_eq!(read.mode, GameMode::Zen);
+        assert_eq!(inputs(&read), [(0.0, 1.0); 3]);
+    }
+
+    #[test]
+    fn version_3() {
+        let mut bytes = b"STSR\x03".to_vec();
+        bytes.extend_from_slice(&42u64.to_le_bytes());
+        bytes.extend_from_slice(&[1, (-64i8) as u8, 51, 4]);
+
+        let read = Replay::from_bytes(&bytes).unwrap();
+        assert_eq!(read.mode, GameMode::TimeAttack);
+        assert!(read.tuning.is_none());
+        assert_eq!(inputs(&read), [(-64.0 / 127.0, 0.2); 4]);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut replay = Replay::new(3, GameMode::Endless, Tuning::default());
+        replay.record(Inputs::new(1.0, 0.0));
+        for _ in 0..200 {
+            replay.record(Inputs::new(-1.0, 1.0));
+        }
+        let bytes = replay.to_bytes();
+
+        // Cutting anywhere but between runs leaves something half written. The runs take 3 and then 4 bytes.
+        for len in 0..bytes.len() {
+            match Replay::from_bytes(&bytes[..len]) {
+                Err(ReplayError::Truncated) => {},
+                Ok(read) if len == bytes.len() - 7 => assert_eq!(read.ticks(), 0),
+                Ok(read) if len == bytes.len() - 4 => assert_eq!(read.ticks(), 1),
+                _ => panic!("{} bytes didn't count as truncated", len),
+            }
+        }
+
+        let mut bytes = b"STSR\x02".to_vec();
+        bytes.extend_from_slice(&42u64.to_le_bytes());
+        assert!(matches!(Replay::from_bytes(&bytes), Err(ReplayError::Truncated)));
+        bytes.extend_from_slice(&[0, 0b001]);
+        assert!(matches!(Replay::from_bytes(&bytes), Err(ReplayError::Truncated)));
+    }
+
+    #[test]
+    fn bad_headers() {
+        let bytes = Replay::new(3, GameMode::Endless, Tuning::default()).to_bytes();
+        assert!(matches!(Replay::from_bytes(&[b"XXXX", &bytes[4..]].concat()), Err(ReplayError::BadMagic)));
+        assert!(matches!(Replay::from_bytes(&[b"STSR\x05", &bytes[5..]].concat()), Err(ReplayError::BadVersion(5))));
+        assert!(matches!(Replay::from_bytes(&[&bytes[..13], &[9]].concat()), Err(ReplayError::BadMode(9))));
+    }
+}
@@ -0,0 +1,166 @@
+use specs::{
+    prelude::*,
+    saveload::{SerializeComponents, DeserializeComponents, SimpleMarkerAllocator},
+    error::NoError,
+};
+use serde::{Serialize, Deserialize};
+use crate::{
+    tuning::Tuning,
+    world::{
+        self,
+        Globals, GameMode, Seed, WorldRng, Attr, Seafloor, Saved, SaveMarker,
+        Pos, Vel, Ori, Rot, Agent, Body, Item, Hazard, Collected, Route, Leak, Pursuit,
+    },
+};
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Json(serde_json::Error),
+    NoPlayer,
+    NoSubmarine,
+}
+
+impl From<serde_json::Error> for SnapshotError {
+    fn from(err: serde_json::Error) -> Self {
+        SnapshotError::Json(err)
+    }
+}
+
+// The complete state of a world, enough to carry on simulating it exactly where it left off
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    seed: Seed,
+    rng: WorldRng,
+    attr: Attr,
+    seafloor: Seafloor,
+    tuning: Tuning,
+    mode: GameMode,
+    entities: serde_json::Value,
+}
+
+pub fn save(world: &World) -> Result<Snapshot, SnapshotError> {
+    let entities = SerializeComponents::<NoError, SaveMarker>::serialize(
+        &(
+            world.read_storage::<Pos>(),
+            world.read_storage::<Vel>(),
+            world.read_storage::<Ori>(),
+            world.read_storage::<Rot>(),
+            world.read_storage::<Agent>(),
+            world.read_storage::<Body>(),
+            world.read_storage::<Item>(),
+            world.read_storage::<Hazard>(),
+            world.read_storage::<Collected>(),
+            world.read_storage::<Route>(),
+            world.read_storage::<Leak>(),
+            world.read_storage::<Pursuit>(),
+        ),
+        &world.entities(),
+        &world.read_storage::<SaveMarker>(),
+        serde_json::value::Serializer,
+    )?;
+
+    Ok(Snapshot {
+        seed: (*world.read_resource::<Seed>()).clone(),
+        rng: (*world.read_resource::<WorldRng>()).clone(),
+        attr: (*world.read_resource::<Attr>()).clone(),
+        seafloor: (*world.read_resource::<Seafloor>()).clone(),
+        tuning: (*world.read_resource::<Tuning>()).clone(),
+        mode: *world.read_resource::<GameMode>(),
+        entities,
+    })
+}
+
+pub fn load(snapshot: Snapshot) -> Result<(Globals, World), SnapshotError> {
+    let mut world = world::setup();
+
+    DeserializeComponents::<NoError, SaveMarker>::deserialize(
+        &mut (
+            world.write_storage::<Pos>(),
+            world.write_storage::<Vel>(),
+            world.write_storage::<Ori>(),
+            world.write_storage::<Rot>(),
+            world.write_storage::<Agent>(),
+            world.write_storage::<Body>(),
+            world.write_storage::<Item>(),
+            world.write_storage::<Hazard>(),
+            world.write_storage::<Collected>(),
+            world.write_storage::<Route>(),
+            world.write_storage::<Leak>(),
+            world.write_storage::<Pursuit>(),
+        ),
+        &world.entities(),
+        &mut world.write_storage::<SaveMarker>(),
+        &mut world.write_resource::<SimpleMarkerAllocator<Saved>>(),
+        snapshot.entities,
+    )?;
+
+    world.insert(snapshot.seed);
+    world.insert(snapshot.rng);
+    world.insert(snapshot.attr);
+    world.insert(snapshot.seafloor);
+    world.insert(snapshot.tuning.currents.clone());
+    world.insert(snapshot.tuning);
+    world.insert(snapshot.mode);
+
+    let player = (&world.entities(), &world.read_storage::<Agent>())
+        .join()
+        .find(|(_, agent)| matches!(agent, Agent::Player))
+        .map(|(entity, _)| entity)
+        .ok_or(SnapshotError::NoPlayer)?;
+    let submarine = (&world.entities(), &world.read_storage::<Body>())
+        .join()
+        .find(|(_, body)| matches!(body, Body::Submarine))
+        .map(|(entity, _)| entity)
+        .ok_or(SnapshotError::NoSubmarine)?;
+
+    let globals = Globals {
+        player,
+        submarine,
+    };
+    world.insert(globals);
+
+    Ok((globals, world))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{systems, world::{Inputs, TICK_DURATION}};
+
+    fn inputs(tick: u32) -> Inputs {
+        Inputs::new(((tick / 37) % 5) as f32 * 0.37 - 0.8, ((tick / 91) % 3) as f32 * 0.45)
+    }
+
+    fn run(world: &mut World, ticks: std::ops::Range<u32>) {
+        let mut dispatcher = systems::dispatcher();
+        for tick in ticks {
+            world::tick(world, &mut dispatcher, inputs(tick), tick as f32 * TICK_DURATION);
+        }
+    }
+
+    // Everything that's visible of a world, in an order that doesn't depend on entity ids
+    fn state(world: &World) -> (String, Vec<String>, usize) {
+        let mut positions: Vec<_> = world.read_storage::<Pos>().join().map(|pos| format!("{:?}", pos.0)).collect();
+        positions.sort();
+        (format!("{:?}", *world.read_resource::<Attr>()), positions, world.read_storage::<Collected>().join().count())
+    }
+
+    #[test]
+    fn resumed_runs_match_uninterrupted_ones() {
+        let (_, mut uninterrupted) = world::create(5, Tuning::default(), GameMode::TimeAttack);
+        run(&mut uninterrupted, 0..1500);
+
+        // Make sure there's something being carried
+        let item = (&uninterrupted.entities(), &uninterrupted.read_storage::<Item>()).join().next().unwrap().0;
+        uninterrupted.write_storage::<Collected>().insert(item, Collected {}).unwrap();
+
+        let json = serde_json::to_string(&save(&uninterrupted).unwrap()).unwrap();
+        let (_, mut resumed) = load(serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(state(&resumed), state(&uninterrupted));
+        assert!(state(&resumed).2 > 0);
+
+        run(&mut uninterrupted, 1500..4000);
+        run(&mut resumed, 1500..4000);
+        assert_eq!(state(&resumed), state(&uninterrupted));
+    }
+}
@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use crate::world::{self, Event, Loss, TickInfo};
+
+// Tallies of what happened, either in one run or over every run ever played
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub runs: u32,
+    pub ticks: u64,
+    pub fish_eaten: u32,
+    pub fuel_delivered: u32,
+    pub tape_delivered: u32,
+    pub deepest_dive: f32,
+    pub highest_jump: f32,
+    pub deaths: BTreeMap<Loss, u32>,
+}
+
+impl Stats {
+    pub fn record(&mut self, tick_info: &TickInfo) {
+        self.ticks += 1;
+        for event in tick_info.events.iter() {
+            match event {
+                Event::Eat => self.fish_eaten += 1,
+                Event::GetFuel => self.fuel_delivered += 1,
+                Event::GetTape => self.tape_delivered += 1,
+                _ => {},
+            }
+        }
+
+        // The view follows the seal, and the surface is at zero
+        self.deepest_dive = self.deepest_dive.max(tick_info.view_centre.y);
+        self.highest_jump = self.highest_jump.max(-tick_info.view_centre.y);
+    }
+
+    pub fn record_death(&mut self, loss: Loss) {
+        *self.deaths.entry(loss).or_insert(0) += 1;
+    }
+
+    pub fn seconds(&self) -> f32 {
+        self.ticks as f32 * world::TICK_DURATION
+    }
+}
@@ -0,0 +1,736 @@
+use vek::*;
+use rand::prelude::*;
+use specs::prelude::*;
+use specs::saveload::MarkedBuilder;
+use crate::world::{
+    TICKS_PER_SECOND, Globals, Inputs, Time, TickInfo, Event, Attr, GameMode, Seafloor, OceanCurrents, WorldRng, SaveMarker,
+    Pos, Vel, Ori, Rot, Agent, Body, Item, Hazard, Respawn, Collected, Route, Leak, Pursuit,
+};
+use crate::{grid::SpatialGrid, tuning::Tuning};
+
+const SUB_SPEED: f32 = 2.0;
+// How far the submarine sinks below its route once the hull is completely flooded
+const SUB_SINK_DEPTH: f32 = 700.0;
+const SUB_FLOOR_CLEARANCE: f32 = 250.0;
+
+const MAX_LEAKS: usize = 6;
+
+// How far away predators notice the seal, and how hard they swim after it
+const PREDATOR_SIGHT: f32 = 900.0;
+const PREDATOR_TURN: f32 = 0.06;
+const PREDATOR_REST: u32 = TICKS_PER_SECOND * 3;
+const SHARK_THRUST: f32 = 0.31;
+const ORCA_THRUST: f32 = 0.27;
+const SHARK_BITE: f32 = 0.2;
+const ORCA_KNOCKBACK: f32 = 14.0;
+
+// Hazards too far from the seal get moved back near it
+const HAZARD_RANGE: f32 = 4500.0;
+// Mines only go off when they hit the hull faster than this
+const MINE_ARMED_SPEED: f32 = 1.5;
+const MINE_DAMAGE: f32 = 0.2;
+const JELLYFISH_STING: f32 = 0.08;
+// How hard jellyfish and debris knock the seal away
+const HAZARD_PUSH: f32 = 6.0;
+const DEBRIS_SINK: f32 = 1.2;
+const DEBRIS_HIT: f32 = 0.04;
+const DEBRIS_DENT: f32 = 0.05;
+
+// Including the fish itself
+const FISH_NEIGHBOURS: usize = 3;
+
+// Each system depends on the last one before it that touches the same data, so anything that shares data still runs
+// in this order and the simulation stays deterministic. Almost everything steers by writing velocities, so most of it
+// has to run one after another anyway; the grid can be rebuilt while the steering runs, and attributes tick alongside
+// respawning.
+pub fn dispatcher() -> Dispatcher<'static, 'static> {
+    DispatcherBuilder::new()
+        .with(SeafloorSystem, "seafloor", &[])
+        .with(PhysicsSystem, "physics", &["seafloor"])
+        .with(SubmarineSystem, "submarine", &["physics"])
+        .with(LeakSystem, "leaks", &["submarine"])
+        .with(PlayerControlSystem, "player_control", &["leaks"])
+        .with(FishBoidSystem::default(), "fish_boids", &["player_control"])
+        .with(PredatorSystem, "predators", &["fish_boids"])
+        .with(HazardSystem, "hazards", &["predators"])
+        .with(BubbleSystem, "bubbles", &["hazards"])
+        .with(CollectableSystem, "collectables", &["bubbles"])
+        // Only needs positions, which nothing after the leaks moves
+        .with(GridSystem, "grid", &["leaks"])
+        .with(CollisionSystem, "collision", &["grid", "collectables"])
+        .with(RespawnSystem, "respawn", &["collision"])
+        .with(AttrSystem, "attr", &["collision"])
+        .build()
+}
+
+fn underwater(pos: &Pos) -> bool {
+    pos.0.y > 0.0
+}
+
+// Self-propelled things push themselves along the direction they're facing
+fn swim(pos: &Pos, vel: &mut Vel, ori: &Ori, rot: &mut Rot, time: f32) {
+    if underwater(pos) {
+        vel.0 += Vec2::new(
+            ori.0.cos(),
+            ori.0.sin(),
+        ) * 0.3;
+
+        rot.0 += (time * 10.0).sin() * vel.0.magnitude().sqrt() * 0.001;
+    }
+}
+
+pub struct SeafloorSystem;
+
+impl<'a> System<'a> for SeafloorSystem {
+    type SystemData = (
+        ReadExpect<'a, Globals>,
+        WriteExpect<'a, Seafloor>,
+        ReadStorage<'a, Pos>,
+    );
+
+    fn run(&mut self, (globals, mut seafloor, positions): Self::SystemData) {
+        if let Some(pos) = positions.get(globals.player) {
+            seafloor.generate_around(pos.0.x, 4000.0);
+        }
+    }
+}
+
+pub struct PhysicsSystem;
+
+impl<'a> System<'a> for PhysicsSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadExpect<'a, Tuning>,
+        ReadExpect<'a, Seafloor>,
+        ReadExpect<'a, OceanCurrents>,
+        Write<'a, TickInfo>,
+        WriteStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Ori>,
+        WriteStorage<'a, Rot>,
+        ReadStorage<'a, Agent>,
+        ReadStorage<'a, Collected>,
+    );
+
+    fn run(&mut self, (entities, time, tuning, seafloor, currents, mut tick_info, mut positions, mut velocities, mut oris, mut rots, agents, collected): Self::SystemData) {
+        for (entity, pos, vel, ori, rot) in (&entities, &mut positions, &mut velocities, &mut oris, &mut rots).join() {
+            if underwater(pos) {
+                // Drag
+                vel.0 *= tuning.water_drag;
+                rot.0 *= tuning.water_spin_drag;
+
+                let ori_dir = Vec2::new(
+                    ori.0.cos(),
+                    ori.0.sin(),
+                );
+                vel.0 *= f32::lerp(ori_dir.dot(vel.0.try_normalized().unwrap_or(Vec2::zero())).max((-ori_dir).dot(vel.0.try_normalized().unwrap_or(Vec2::zero()))), 1.0, 0.9);
+            } else {
+                rot.0 *= tuning.air_spin_drag;
+            };
+
+            if !underwater(pos) {
+                vel.0.y += tuning.gravity;
+            }
+
+            // The water carries along the seal, fish, bubbles and anything that hasn't been picked up yet
+            let drifts = match agents.get(entity) {
+                Some(Agent::Player) | Some(Agent::Fish) | Some(Agent::Bubble) => true,
+                Some(Agent::Collectable) => collected.get(entity).is_none(),
+                _ => false,
+            };
+            let drift = if drifts && underwater(pos) { currents.sample(pos.0, time.0) } else { Vec2::zero() };
+
+            let is_underwater = underwater(pos);
+            pos.0 += vel.0 + drift;
+            let is_underwater2 = underwater(pos);
+
+            if is_underwater ^ is_underwater2 && vel.0.y.abs() > 4.0 {
+                tick_info.events.push(Event::Splash(pos.0.x));
+            }
+
+            ori.0 += rot.0;
+
+            // Collision with seafloor
+            while pos.0.y > seafloor.sample(pos.0.x) {
+                pos.0 += seafloor.normal_at(pos.0.x) * 0.5;
+                vel.0 += seafloor.normal_at(pos.0.x) * 0.1;
+                vel.0 *= 0.97;
+            }
+        }
+    }
+}
+
+pub struct SubmarineSystem;
+
+impl<'a> System<'a> for SubmarineSystem {
+    type SystemData = (
+        ReadExpect<'a, Attr>,
+        ReadExpect<'a, Seafloor>,
+        WriteExpect<'a, WorldRng>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Route>,
+        ReadStorage<'a, Agent>,
+    );
+
+    fn run(&mut self, (attr, seafloor, mut rng, positions, mut velocities, mut routes, agents): Self::SystemData) {
+        for (pos, vel, route, _) in (&positions, &mut velocities, &mut routes, &agents)
+            .join()
+            .filter(|(_, _, _, agent)| matches!(agent, Agent::Submarine))
+        {
+            // Plan the route a few waypoints ahead, occasionally turning back on itself
+            while route.waypoints.len() < 3 {
+                let last = route.waypoints.back().copied().unwrap_or(pos.0);
+                if rng.0.gen_bool(0.25) {
+                    route.heading = -route.heading;
+                }
+                let x = last.x + rng.0.gen_range(1500.0, 3000.0) * route.heading;
+                let y = rng.0.gen_range(300.0f32, 800.0).min(seafloor.sample(x) - SUB_FLOOR_CLEARANCE);
+                route.waypoints.push_back(Vec2::new(x, y));
+            }
+
+            let target = route.waypoints[0];
+            if (target.x - pos.0.x).abs() < 50.0 {
+                route.waypoints.pop_front();
+            }
+
+            // Less fuel means less speed, and a flooded hull drags it down
+            let speed = SUB_SPEED * attr.fuel.sqrt();
+            let depth = (target.y + (1.0 - attr.hull) * SUB_SINK_DEPTH).min(seafloor.sample(pos.0.x) - SUB_FLOOR_CLEARANCE);
+
+            vel.0.x = (target.x - pos.0.x).clamp(-1.0, 1.0) * speed;
+            vel.0.y = ((depth - pos.0.y) * 0.01).clamp(-1.0, 1.0);
+        }
+    }
+}
+
+pub struct LeakSystem;
+
+impl<'a> System<'a> for LeakSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, LazyUpdate>,
+        Read<'a, Time>,
+        ReadExpect<'a, GameMode>,
+        ReadExpect<'a, Globals>,
+        WriteExpect<'a, Attr>,
+        WriteExpect<'a, WorldRng>,
+        WriteStorage<'a, Pos>,
+        ReadStorage<'a, Vel>,
+        ReadStorage<'a, Leak>,
+    );
+
+    fn run(&mut self, (entities, lazy, time, mode, globals, mut attr, mut rng, mut positions, velocities, leaks): Self::SystemData) {
+        let sub_pos = match positions.get(globals.submarine) {
+            Some(pos) => pos.0,
+            None => return,
+        };
+        let facing = match velocities.get(globals.submarine) {
+            Some(vel) if vel.0.x < 0.0 => Vec2::new(-1.0, 1.0),
+            _ => Vec2::one(),
+        };
+
+        // Leaks move with the hull, and each one lets more water in
+        attr.flooding = 0.0;
+        let mut count = 0;
+        for (pos, leak) in (&mut positions, &leaks).join() {
+            pos.0 = sub_pos + leak.offset * facing;
+            attr.flooding += leak.rate;
+            count += 1;
+        }
+
+        // New leaks spring up more often as time goes on
+        if *mode != GameMode::Zen && count < MAX_LEAKS && rng.0.gen_bool((0.001 + 0.000005 * time.0 as f64).min(1.0)) {
+            let leak = Leak::random(&mut rng.0, time.0);
+            lazy
+                .create_entity(&entities)
+                .with(Pos(sub_pos + leak.offset * facing))
+                .with(Body::Leak)
+                .with(leak)
+                .marked::<SaveMarker>()
+                .build();
+        }
+    }
+}
+
+pub struct PlayerControlSystem;
+
+impl<'a> System<'a> for PlayerControlSystem {
+    type SystemData = (
+        Read<'a, Inputs>,
+        Read<'a, Time>,
+        ReadExpect<'a, Tuning>,
+        WriteExpect<'a, Attr>,
+        Write<'a, TickInfo>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        ReadStorage<'a, Ori>,
+        WriteStorage<'a, Rot>,
+        ReadStorage<'a, Agent>,
+    );
+
+    fn run(&mut self, (inputs, time, tuning, mut attr, mut tick_info, positions, mut velocities, oris, mut rots, agents): Self::SystemData) {
+        for (pos, vel, ori, rot, _) in (&positions, &mut velocities, &oris, &mut rots, &agents)
+            .join()
+            .filter(|(_, _, _, _, agent)| matches!(agent, Agent::Player))
+        {
+            let drained = attr.is_drained();
+
+            // User input
+            let score_multi = if underwater(pos) {
+                rot.0 += tuning.turn_rate_water * inputs.steer;
+
+                if inputs.boost > 0.0 { vel.0 *= 1.0 + 0.025 * inputs.boost; attr.stamina -= 0.0003 * inputs.boost; }
+                attr.oxygen -= 1.0 / (tuning.breath_seconds * TICKS_PER_SECOND as f32);
+                1.0
+            } else {
+                rot.0 += tuning.turn_rate_air * inputs.steer;
+                attr.oxygen += 1.0 / (tuning.recover_seconds * TICKS_PER_SECOND as f32);
+                2.0
+            };
+
+            if !drained {
+                attr.score += (0.03 + attr.score.powf(0.5) * 0.001) * score_multi;
+            }
+
+            // Tick info
+            tick_info.view_centre = pos.0;
+            tick_info.view_scale = 1.0;// + 1.0 / (1.0 + vel.0.magnitude());
+
+            swim(pos, vel, ori, rot, time.0);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FishBoidSystem {
+    // Indices into this tick's fish positions and velocities
+    grid: SpatialGrid<usize>,
+}
+
+impl<'a> System<'a> for FishBoidSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        WriteExpect<'a, WorldRng>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Ori>,
+        WriteStorage<'a, Rot>,
+        ReadStorage<'a, Agent>,
+    );
+
+    fn run(&mut self, (time, mut rng, positions, mut velocities, mut oris, mut rots, agents): Self::SystemData) {
+        let is_fish = |agent: &Agent| matches!(agent, Agent::Fish);
+
+        // Find all positions and velocities
+        let all_pos_vel = (&positions, &velocities, &agents)
+            .join()
+            .filter(|(_, _, agent)| is_fish(agent))
+            .map(|(p, v, _)| (p.0, v.0))
+            .collect::<Vec<_>>();
+
+        self.grid.clear();
+        for (i, (pos, _)) in all_pos_vel.iter().enumerate() {
+            self.grid.insert(i, *pos, 0.0);
+        }
+
+        for (pos, vel, ori, rot, _) in (&positions, &mut velocities, &mut oris, &mut rots, &agents)
+            .join()
+            .filter(|(_, _, _, _, agent)| is_fish(agent))
+        {
+            let neighbours = self.grid
+                .nearest(pos.0, FISH_NEIGHBOURS)
+                .into_iter()
+                .map(|i| all_pos_vel[i])
+                .collect::<Vec<_>>();
+
+            let (total_vel, n) = neighbours.iter().fold((Vec2::zero(), 0.0), |(tv, n), (_, v)| (tv + v, n + 1.0));
+            let avg_vel = total_vel / n;
+
+            let (total_pos, n) = neighbours.iter().fold((Vec2::zero(), 0.0), |(tp, n), (p, _)| (tp + p, n + 1.0));
+            let avg_pos = total_pos / n;
+
+            let shy_dir = neighbours.iter().fold(Vec2::zero(), |a, (p, _)| a + (pos.0 - p).try_normalized().unwrap_or(Vec2::zero()) * (100.0 - (pos.0 - p).magnitude()).max(0.0));
+            let dir = Vec2::lerp((avg_pos - pos.0).normalized(), avg_vel, 0.75) + shy_dir * 0.15;
+            let dir = (dir.map(|e| e + rng.0.gen_range(-0.05, 0.05)) - (pos.0 - Vec2::new(0.0, 650.0)) * Vec2::new(1.0, 4.5) * 0.0003).normalized();
+
+            let dir = Lerp::lerp(Vec2::new(ori.0.cos(), ori.0.sin()), dir, 0.1).try_normalized().unwrap_or(Vec2::zero());
+            ori.0 = dir.y.atan2(dir.x);
+
+            swim(pos, vel, ori, rot, time.0);
+        }
+    }
+}
+
+pub struct PredatorSystem;
+
+impl<'a> System<'a> for PredatorSystem {
+    type SystemData = (
+        ReadExpect<'a, Globals>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Ori>,
+        ReadStorage<'a, Body>,
+        WriteStorage<'a, Pursuit>,
+    );
+
+    fn run(&mut self, (globals, positions, mut velocities, mut oris, bodies, mut pursuits): Self::SystemData) {
+        let (player_pos, player_vel) = match (positions.get(globals.player), velocities.get(globals.player)) {
+            (Some(pos), Some(vel)) => (pos.0, vel.0),
+            _ => return,
+        };
+
+        for (pos, vel, ori, body, pursuit) in (&positions, &mut velocities, &mut oris, &bodies, &mut pursuits).join() {
+            let thrust = match body {
+                Body::Orca => ORCA_THRUST,
+                _ => SHARK_THRUST,
+            };
+
+            pursuit.rest = pursuit.rest.saturating_sub(1);
+            let to_player = player_pos - pos.0;
+            let hunting = pursuit.rest == 0
+                && underwater(&Pos(player_pos))
+                && to_player.magnitude() < PREDATOR_SIGHT;
+
+            let (target, thrust) = if hunting {
+                // Head for where the seal will be by the time we get there
+                let lead = (to_player.magnitude() / (thrust * 20.0)).min(60.0);
+                (player_pos + player_vel * lead, thrust)
+            } else if pursuit.rest > 0 {
+                (pos.0 - to_player, thrust * 0.75)
+            } else {
+                // Lurk in the depths off to one side of the seal
+                let side = if to_player.x > 0.0 { -1.0 } else { 1.0 };
+                (Vec2::new(player_pos.x + side * PREDATOR_SIGHT * 1.3, 800.0), thrust * 0.5)
+            };
+            // Predators stay in the water, which is the seal's one escape
+            let target = Vec2::new(target.x, target.y.max(100.0));
+
+            let dir = (target - pos.0).try_normalized().unwrap_or(Vec2::unit_x());
+            let dir = Lerp::lerp(Vec2::new(ori.0.cos(), ori.0.sin()), dir, PREDATOR_TURN).try_normalized().unwrap_or(Vec2::unit_x());
+            ori.0 = dir.y.atan2(dir.x);
+
+            if underwater(pos) {
+                vel.0 += dir * thrust;
+            }
+        }
+    }
+}
+
+pub struct HazardSystem;
+
+impl<'a> System<'a> for HazardSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadExpect<'a, Globals>,
+        ReadExpect<'a, Seafloor>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Rot>,
+        ReadStorage<'a, Hazard>,
+        WriteStorage<'a, Respawn>,
+    );
+
+    fn run(&mut self, (entities, time, globals, seafloor, positions, mut velocities, mut rots, hazards, mut respawns): Self::SystemData) {
+        let player_x = positions.get(globals.player).map(|p| p.0.x).unwrap_or(0.0);
+
+        for (entity, pos, vel, rot, hazard) in (&entities, &positions, &mut velocities, &mut rots, &hazards).join() {
+            match hazard {
+                Hazard::Mine => {
+                    vel.0.y += (time.0 + pos.0.x * 0.01).sin() * 0.02;
+                },
+                Hazard::Jellyfish => {
+                    // Pulse upwards every so often, sinking slowly in between
+                    let pulse = (time.0 * 1.5 + pos.0.x * 0.01).sin().max(0.0);
+                    vel.0.x = (time.0 * 0.5 + pos.0.y * 0.01).sin() * 0.3;
+                    vel.0.y = if pos.0.y < 100.0 { 0.6 } else { 0.6 - pulse * 2.0 };
+                },
+                Hazard::Debris => {
+                    vel.0 = Vec2::new((time.0 + pos.0.y * 0.01).sin() * 0.5, DEBRIS_SINK);
+                    rot.0 = vel.0.x * 0.01;
+                    if pos.0.y > seafloor.sample(pos.0.x) - 40.0 {
+                        respawns.insert(entity, Respawn).unwrap();
+                    }
+                },
+            }
+
+            if (pos.0.x - player_x).abs() > HAZARD_RANGE {
+                respawns.insert(entity, Respawn).unwrap();
+            }
+        }
+    }
+}
+
+pub struct BubbleSystem;
+
+impl<'a> System<'a> for BubbleSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        ReadStorage<'a, Agent>,
+        WriteStorage<'a, Respawn>,
+    );
+
+    fn run(&mut self, (entities, time, positions, mut velocities, agents, mut respawns): Self::SystemData) {
+        for (entity, pos, vel, _) in (&entities, &positions, &mut velocities, &agents)
+            .join()
+            .filter(|(_, _, _, agent)| matches!(agent, Agent::Bubble))
+        {
+            vel.0.x = (time.0 + pos.0.x * 0.01).sin();
+            vel.0.y = -1.0;
+
+            if !underwater(pos) {
+                respawns.insert(entity, Respawn).unwrap();
+            }
+        }
+    }
+}
+
+pub struct CollectableSystem;
+
+impl<'a> System<'a> for CollectableSystem {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        ReadExpect<'a, Globals>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Ori>,
+        WriteStorage<'a, Rot>,
+        ReadStorage<'a, Agent>,
+        ReadStorage<'a, Collected>,
+    );
+
+    fn run(&mut self, (entities, time, globals, positions, mut velocities, mut oris, mut rots, agents, collected): Self::SystemData) {
+        let player_pos = positions.get(globals.player).map(|p| p.0).unwrap_or(Vec2::zero());
+
+        for (entity, pos, vel, ori, rot, _) in (&entities, &positions, &mut velocities, &mut oris, &mut rots, &agents)
+            .join()
+            .filter(|(_, _, _, _, _, agent)| matches!(agent, Agent::Collectable))
+        {
+            if collected.get(entity).is_some() {
+                let dir = (player_pos - pos.0).try_normalized().unwrap_or(Vec2::unit_y());
+                ori.0 = dir.y.atan2(dir.x);
+                if underwater(pos) {
+                    vel.0 *= 1.02;
+                } else {
+                    vel.0 *= 0.98;
+                }
+            } else {
+                vel.0 = Vec2::zero();
+            }
+
+            swim(pos, vel, ori, rot, time.0);
+        }
+    }
+}
+
+pub struct GridSystem;
+
+impl<'a> System<'a> for GridSystem {
+    type SystemData = (
+        Entities<'a>,
+        Write<'a, SpatialGrid>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, Body>,
+    );
+
+    fn run(&mut self, (entities, mut grid, positions, bodies): Self::SystemData) {
+        grid.clear();
+        for (entity, pos, body) in (&entities, &positions, &bodies).join() {
+            grid.insert(entity, pos.0, body.radius());
+        }
+    }
+}
+
+pub struct CollisionSystem;
+
+impl<'a> System<'a> for CollisionSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Globals>,
+        ReadExpect<'a, Tuning>,
+        Read<'a, SpatialGrid>,
+        WriteExpect<'a, Attr>,
+        Write<'a, TickInfo>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, Body>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Leak>,
+        ReadStorage<'a, Hazard>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Pursuit>,
+        WriteStorage<'a, Respawn>,
+        WriteStorage<'a, Collected>,
+    );
+
+    fn run(&mut self, (entities, globals, tuning, grid, mut attr, mut tick_info, positions, bodies, items, leaks, hazards, mut velocities, mut pursuits, mut respawns, mut collected): Self::SystemData) {
+        if let (Some(pos), Some(body)) = (positions.get(globals.player), bodies.get(globals.player)) {
+            let player_vel = velocities.get(globals.player).map(|v| v.0).unwrap_or(Vec2::zero());
+            let mut push = Vec2::zero();
+
+            for other_entity in grid.within(pos.0, body.radius()) {
+                // Predators only go for the seal when they're not backing off from the last attempt
+                if let (Some(other_pos), Some(pursuit)) = (positions.get(other_entity), pursuits.get_mut(other_entity)) {
+                    if pursuit.rest == 0 {
+                        pursuit.rest = PREDATOR_REST;
+                        match bodies.get(other_entity) {
+                            Some(Body::Orca) => {
+                                push += (pos.0 - other_pos.0).try_normalized().unwrap_or(Vec2::unit_y()) * ORCA_KNOCKBACK;
+                                tick_info.events.push(Event::Ram);
+                            },
+                            _ => {
+                                attr.stamina -= SHARK_BITE;
+                                tick_info.events.push(Event::Bite);
+                            },
+                        }
+                    }
+                }
+
+                let other_pos = positions.get(other_entity).map(|p| p.0).unwrap_or(pos.0);
+                let away = (pos.0 - other_pos).try_normalized().unwrap_or(Vec2::unit_y());
+                match hazards.get(other_entity) {
+                    Some(Hazard::Mine) => {
+                        if let Some(vel) = velocities.get_mut(other_entity) {
+                            vel.0 += player_vel * 0.8;
+                        }
+                    },
+                    // Only sting when swimming into the jellyfish, so that the push away doesn't sting twice
+                    Some(Hazard::Jellyfish) if player_vel.dot(away) < 0.0 => {
+                        push += away * HAZARD_PUSH;
+                        attr.stamina -= JELLYFISH_STING;
+                        tick_info.events.push(Event::Sting);
+                    },
+                    Some(Hazard::Debris) => {
+                        respawns.insert(other_entity, Respawn).unwrap();
+                        push += away * HAZARD_PUSH;
+                        attr.stamina -= DEBRIS_HIT;
+                        tick_info.events.push(Event::Clang);
+                    },
+                    _ => {},
+                }
+
+                // Bubbles are a gulp of air
+                if let Some(Body::Bubble(_)) = bodies.get(other_entity) {
+                    respawns.insert(other_entity, Respawn).unwrap();
+                    attr.oxygen += tuning.bubble_oxygen;
+                    tick_info.events.push(Event::Breathe);
+                }
+
+                match items.get(other_entity) {
+                    Some(Item::Fish) => {
+                        respawns.insert(other_entity, Respawn).unwrap();
+                        attr.stamina += tuning.fish_stamina;
+                        tick_info.events.push(Event::Eat);
+                    },
+                    Some(Item::Fuel) | Some(Item::Tape) => {
+                        collected.insert(other_entity, Collected {}).unwrap();
+                    },
+                    None => {},
+                }
+            }
+
+            if let Some(vel) = velocities.get_mut(globals.player) {
+                vel.0 += push;
+            }
+        }
+
+        // Things reach the submarine when they're close in an ellipse around the hull
+        if let Some(sub_pos) = positions.get(globals.submarine).map(|p| p.0) {
+            for entity in grid.within(sub_pos, 500.0) {
+                let at_sub = positions
+                    .get(entity)
+                    .map(|pos| ((sub_pos - pos.0) * Vec2::new(1.0, 3.0)).magnitude_squared() < 500.0f32.powf(2.0))
+                    .unwrap_or(false);
+                if !at_sub {
+                    continue;
+                }
+
+                match (bodies.get(entity), hazards.get(entity)) {
+                    // The submarine moves, so it mustn't be able to pick things up without the player's help
+                    (Some(Body::Fuel(_)), _) if collected.get(entity).is_some() => {
+                        respawns.insert(entity, Respawn).unwrap();
+                        attr.fuel += tuning.fuel_boost;
+                        tick_info.events.push(Event::GetFuel);
+                    },
+                    // Likewise, it can sail straight past a mine that's sitting still
+                    (_, Some(Hazard::Mine)) if velocities.get(entity).map(|v| v.0.magnitude() > MINE_ARMED_SPEED).unwrap_or(false) => {
+                        respawns.insert(entity, Respawn).unwrap();
+                        attr.hull -= MINE_DAMAGE;
+                        tick_info.events.push(Event::Explode(positions.get(entity).unwrap().0.x));
+                    },
+                    (_, Some(Hazard::Debris)) => {
+                        respawns.insert(entity, Respawn).unwrap();
+                        attr.hull -= DEBRIS_DENT;
+                        tick_info.events.push(Event::Clang);
+                    },
+                    _ => {},
+                }
+            }
+        }
+
+        // Tape has to be carried to a leak to patch it
+        for (leak_entity, leak_pos, leak_body, _) in (&entities, &positions, &bodies, &leaks).join() {
+            let tape = grid
+                .within(leak_pos.0, leak_body.radius())
+                .into_iter()
+                .find(|entity| match items.get(*entity) {
+                    Some(Item::Tape) => collected.get(*entity).is_some() && respawns.get(*entity).is_none(),
+                    _ => false,
+                });
+
+            if let Some(tape) = tape {
+                respawns.insert(tape, Respawn).unwrap();
+                entities.delete(leak_entity).unwrap();
+                attr.hull += tuning.tape_boost;
+                tick_info.events.push(Event::GetTape);
+            }
+        }
+    }
+}
+
+pub struct RespawnSystem;
+
+impl<'a> System<'a> for RespawnSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Globals>,
+        ReadExpect<'a, Seafloor>,
+        WriteExpect<'a, WorldRng>,
+        WriteStorage<'a, Pos>,
+        WriteStorage<'a, Respawn>,
+        WriteStorage<'a, Collected>,
+    );
+
+    fn run(&mut self, (entities, globals, seafloor, mut rng, mut positions, mut respawns, mut collected): Self::SystemData) {
+        // Things respawn around the player, wherever they've got to
+        let centre_x = positions.get(globals.player).map(|p| p.0.x).unwrap_or(0.0);
+
+        for (entity, pos, _) in (&entities, &mut positions, &respawns).join() {
+            let x = centre_x + rng.0.gen_range(-3500.0, 3500.0);
+            let y: f32 = rng.0.gen_range(-300.0, 1500.0);
+            pos.0 = Vec2::new(x, y.min(seafloor.sample(x) - 20.0));
+            collected.remove(entity);
+        }
+        respawns.clear();
+    }
+}
+
+pub struct AttrSystem;
+
+impl<'a> System<'a> for AttrSystem {
+    type SystemData = (
+        Read<'a, Time>,
+        ReadExpect<'a, Tuning>,
+        ReadExpect<'a, GameMode>,
+        WriteExpect<'a, Attr>,
+    );
+
+    fn run(&mut self, (time, tuning, mode, mut attr): Self::SystemData) {
+        attr.tick(time.0, &tuning, *mode);
+    }
+}
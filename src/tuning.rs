@@ -0,0 +1,94 @@
+use serde::{Serialize, Deserialize};
+use crate::world::OceanCurrents;
+
+// Gameplay numbers that can be rebalanced in static/tuning.json without recompiling. Anything missing from the file
+// keeps its default.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tuning {
+    pub fish_stamina: f32,
+    pub fuel_boost: f32,
+    pub tape_boost: f32,
+    pub bubble_oxygen: f32,
+
+    pub turn_rate_water: f32,
+    pub turn_rate_air: f32,
+    pub gravity: f32,
+    // Velocity and spin get multiplied by these every tick
+    pub water_drag: f32,
+    pub water_spin_drag: f32,
+    pub air_spin_drag: f32,
+
+    // Stamina and fuel drain by `decay + decay_growth * time` every tick, so things get harder as a run goes on
+    pub decay: f32,
+    pub decay_growth: f32,
+    // How long a full breath lasts underwater, and how long it takes to get back at the surface
+    pub breath_seconds: f32,
+    pub recover_seconds: f32,
+
+    pub spawns: Spawns,
+    pub currents: OceanCurrents,
+}
+
+impl Tuning {
+    pub fn from_json(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            fish_stamina: 0.15,
+            fuel_boost: 0.25,
+            tape_boost: 0.25,
+            bubble_oxygen: 0.1,
+
+            turn_rate_water: 0.0065,
+            turn_rate_air: 0.001,
+            gravity: 0.1,
+            water_drag: 0.95,
+            water_spin_drag: 0.90,
+            air_spin_drag: 0.98,
+
+            decay: 0.0001,
+            decay_growth: 0.0000025,
+            breath_seconds: 25.0,
+            recover_seconds: 2.0,
+
+            spawns: Spawns::default(),
+            currents: OceanCurrents::default(),
+        }
+    }
+}
+
+// How many of each thing a new world starts with
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Spawns {
+    pub fish: usize,
+    pub fuel: usize,
+    pub tape: usize,
+    pub bubbles: usize,
+    pub sharks: usize,
+    pub orcas: usize,
+    pub mines: usize,
+    pub jellyfish: usize,
+    pub debris: usize,
+}
+
+impl Default for Spawns {
+    fn default() -> Self {
+        Self {
+            fish: 30,
+            fuel: 15,
+            tape: 15,
+            bubbles: 100,
+            sharks: 2,
+            orcas: 1,
+            mines: 6,
+            jellyfish: 12,
+            debris: 6,
+        }
+    }
+}
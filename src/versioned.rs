@@ -0,0 +1,218 @@
+use std::io::ErrorKind;
+use quicksilver::saving::{save, load, save_raw, load_raw, SaveError};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use seal_the_sub::daily;
+
+// Turns the layout of one version into the next. The first migration upgrades saves from before versioning, which
+// count as version 0, and the current version is the number of migrations.
+pub type Migration = fn(Value) -> Option<Value>;
+
+// Somewhere saves can be kept, so that tests don't need the real ones
+pub trait Storage {
+    fn load(&self, key: &str) -> Result<Value, SaveError>;
+    fn load_raw(&self, key: &str) -> Result<Vec<u8>, SaveError>;
+    fn save(&mut self, key: &str, value: &Value) -> Result<(), SaveError>;
+    fn save_raw(&mut self, key: &str, bytes: &[u8]) -> Result<(), SaveError>;
+
+    // Anything at all, even if it can't be read
+    fn exists(&self, key: &str) -> bool {
+        match self.load_raw(key) {
+            Ok(_) | Err(SaveError::DecodeError) => true,
+            Err(_) => false,
+        }
+    }
+}
+
+// Wherever quicksilver keeps saves on this platform
+pub struct Device;
+
+impl Storage for Device {
+    fn load(&self, key: &str) -> Result<Value, SaveError> {
+        load("seal-the-sub", key)
+    }
+
+    fn load_raw(&self, key: &str) -> Result<Vec<u8>, SaveError> {
+        load_raw("seal-the-sub", key)
+    }
+
+    fn save(&mut self, key: &str, value: &Value) -> Result<(), SaveError> {
+        save("seal-the-sub", key, value)
+    }
+
+    fn save_raw(&mut self, key: &str, bytes: &[u8]) -> Result<(), SaveError> {
+        save_raw("seal-the-sub", key, bytes)
+    }
+}
+
+fn is_missing(err: &SaveError) -> bool {
+    match err {
+        SaveError::SaveNotFound(_) => true,
+        SaveError::IOError(err) => err.kind() == ErrorKind::NotFound,
+        _ => false,
+    }
+}
+
+#[derive(Serialize)]
+struct Tagged<'a, T> {
+    version: usize,
+    data: &'a T,
+}
+
+pub fn save_versioned<T: Serialize>(storage: &mut impl Storage, key: &str, migrations: &[Migration], data: &T) -> Result<(), SaveError> {
+    storage.save(key, &serde_json::to_value(Tagged { version: migrations.len(), data })?)
+}
+
+// None if there's nothing saved yet. Anything that's there but can't be read gets backed up first, so that a bad
+// release can't lose anyone's progress. If that fails then so does this, and the caller mustn't save over it.
+pub fn load_versioned<T: DeserializeOwned>(storage: &mut impl Storage, key: &str, migrations: &[Migration]) -> Result<Option<T>, SaveError> {
+    let value = match storage.load(key) {
+        Ok(Value::Null) => return Ok(None),
+        Ok(value) => value,
+        Err(err) if is_missing(&err) => return Ok(None),
+        Err(SaveError::SerdeError(_)) => {
+            let bytes = storage.load_raw(key)?;
+            let backup = backup_key(storage, key);
+            storage.save_raw(&backup, &bytes)?;
+            return Ok(None);
+        },
+        Err(err) => return Err(err),
+    };
+
+    match upgrade(value.clone(), migrations).and_then(|data| serde_json::from_value(data).ok()) {
+        Some(data) => Ok(Some(data)),
+        None => {
+            let backup = backup_key(storage, key);
+            storage.save(&backup, &value)?;
+            Ok(None)
+        },
+    }
+}
+
+fn upgrade(value: Value, migrations: &[Migration]) -> Option<Value> {
+    let (version, mut data) = match value {
+        Value::Object(mut tagged) if tagged.contains_key("version") && tagged.contains_key("data") => (
+            tagged.get("version")?.as_u64()? as usize,
+            tagged.remove("data")?,
+        ),
+        data => (0, data),
+    };
+
+    // Saves from a newer version than this one can't be read
+    for migration in migrations.get(version..)? {
+        data = migration(data)?;
+    }
+    Some(data)
+}
+
+// Numbered after the first of the day, so that one backup never replaces another
+fn backup_key(storage: &impl Storage, key: &str) -> String {
+    let backup = format!("{}-backup-{}", key, daily::date(daily::today()));
+    std::iter::once(backup.clone())
+        .chain((2..).map(|n| format!("{}-{}", backup, n)))
+        .find(|key| !storage.exists(key))
+        .unwrap()
+}
+
+// Saves kept in memory, which can be made to fail every write
+#[cfg(test)]
+#[derive(Default)]
+pub struct Memory {
+    pub saves: std::collections::BTreeMap<String, Vec<u8>>,
+    pub read_only: bool,
+}
+
+#[cfg(test)]
+impl Storage for Memory {
+    fn load(&self, key: &str) -> Result<Value, SaveError> {
+        Ok(serde_json::from_slice(&self.load_raw(key)?)?)
+    }
+
+    fn load_raw(&self, key: &str) -> Result<Vec<u8>, SaveError> {
+        self.saves.get(key).cloned().ok_or_else(|| SaveError::SaveNotFound(key.to_string()))
+    }
+
+    fn save(&mut self, key: &str, value: &Value) -> Result<(), SaveError> {
+        let bytes = serde_json::to_vec(value)?;
+        self.save_raw(key, &bytes)
+    }
+
+    fn save_raw(&mut self, key: &str, bytes: &[u8]) -> Result<(), SaveError> {
+        if self.read_only {
+            return Err(SaveError::SaveWriteFailed);
+        }
+        self.saves.insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rename(mut data: Value) -> Option<Value> {
+        let old = data.as_object_mut()?.remove("old")?;
+        data.as_object_mut()?.insert("new".to_string(), old);
+        Some(data)
+    }
+
+    const MIGRATIONS: &[Migration] = &[rename];
+
+    fn backup() -> String {
+        format!("data-backup-{}", daily::date(daily::today()))
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut storage = Memory::default();
+        save_versioned(&mut storage, "data", MIGRATIONS, &json!({ "new": 1 })).unwrap();
+        assert_eq!(storage.load("data").unwrap(), json!({ "version": 1, "data": { "new": 1 } }));
+        assert_eq!(load_versioned::<Value>(&mut storage, "data", MIGRATIONS).unwrap(), Some(json!({ "new": 1 })));
+    }
+
+    #[test]
+    fn nothing_saved() {
+        let mut storage = Memory::default();
+        assert_eq!(load_versioned::<Value>(&mut storage, "data", MIGRATIONS).unwrap(), None);
+        storage.save("data", &Value::Null).unwrap();
+        assert_eq!(load_versioned::<Value>(&mut storage, "data", MIGRATIONS).unwrap(), None);
+        assert!(!storage.exists(&backup()));
+    }
+
+    #[test]
+    fn untagged_saves_are_version_0() {
+        let mut storage = Memory::default();
+        storage.save("data", &json!({ "old": 1 })).unwrap();
+        assert_eq!(load_versioned::<Value>(&mut storage, "data", MIGRATIONS).unwrap(), Some(json!({ "new": 1 })));
+    }
+
+    #[test]
+    fn newer_versions_are_backed_up() {
+        let mut storage = Memory::default();
+        let newer = json!({ "version": 2, "data": { "newer": 1 } });
+        storage.save("data", &newer).unwrap();
+        assert_eq!(load_versioned::<Value>(&mut storage, "data", MIGRATIONS).unwrap(), None);
+        assert_eq!(storage.load(&backup()).unwrap(), newer);
+        assert_eq!(storage.load("data").unwrap(), newer);
+    }
+
+    #[test]
+    fn unparseable_saves_are_backed_up_without_replacing_other_backups() {
+        let mut storage = Memory::default();
+        storage.save_raw("data", b"{ not json").unwrap();
+        load_versioned::<Value>(&mut storage, "data", MIGRATIONS).unwrap();
+        storage.save_raw("data", b"still not json").unwrap();
+        load_versioned::<Value>(&mut storage, "data", MIGRATIONS).unwrap();
+        assert_eq!(storage.load_raw(&backup()).unwrap(), b"{ not json");
+        assert_eq!(storage.load_raw(&format!("{}-2", backup())).unwrap(), b"still not json");
+    }
+
+    #[test]
+    fn failed_backups_are_errors() {
+        let mut storage = Memory::default();
+        storage.save("data", &json!({ "version": 2, "data": {} })).unwrap();
+        storage.read_only = true;
+        assert!(load_versioned::<Value>(&mut storage, "data", MIGRATIONS).is_err());
+    }
+}
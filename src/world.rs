@@ -1,27 +1,119 @@
+use std::collections::{HashMap, VecDeque};
 use vek::*;
-use rand::{prelude::*, thread_rng};
+use rand::prelude::*;
+use rand_pcg::Pcg32;
 use specs::{
     prelude::*,
+    saveload::{MarkedBuilder, SimpleMarker, SimpleMarkerAllocator},
     Component,
 };
-use crate::game::Inputs;
+use serde::{Serialize, Deserialize};
+use crate::{grid::SpatialGrid, tuning::Tuning};
 
 pub use specs::World;
 
+// Steering goes from -1 (hard left) to 1 (hard right), and boost from 0 to 1. Make them with `Inputs::new`, which rounds
+// them to what a replay can store, or replaying a run won't give the same result.
+#[derive(Copy, Clone, Default, PartialEq)]
+pub struct Inputs {
+    pub steer: f32,
+    pub boost: f32,
+}
+
+impl Inputs {
+    pub const STEER_STEPS: f32 = 127.0;
+    pub const BOOST_STEPS: f32 = 255.0;
+
+    pub fn new(steer: f32, boost: f32) -> Self {
+        Self {
+            steer: (steer.clamp(-1.0, 1.0) * Self::STEER_STEPS).round() / Self::STEER_STEPS,
+            boost: (boost.clamp(0.0, 1.0) * Self::BOOST_STEPS).round() / Self::BOOST_STEPS,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
 pub struct Globals {
     pub player: Entity,
     pub submarine: Entity,
 }
 
-const FISH_STAMINA: f32 = 0.15;
-const FUEL_BOOST: f32 = 0.25;
-const TAPE_BOOST: f32 = 0.25;
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Seed(pub u64);
+
+#[derive(Default)]
+pub struct Time(pub f32);
+
+// Everything random in the world must come from here, or runs stop being reproducible
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorldRng(pub Pcg32);
+
+// Entities with this marker get included in snapshots
+pub struct Saved;
+pub type SaveMarker = SimpleMarker<Saved>;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GameMode {
+    // Survive for as long as possible
+    #[default]
+    Endless,
+    // Score as much as possible before time runs out
+    TimeAttack,
+    // Nothing runs out and nothing attacks, so there's just swimming
+    Zen,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Endless, GameMode::TimeAttack, GameMode::Zen];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::TimeAttack => "Time Attack",
+            GameMode::Zen => "Zen",
+        }
+    }
+
+    // In seconds
+    pub fn time_limit(&self) -> Option<f32> {
+        match self {
+            GameMode::TimeAttack => Some(180.0),
+            _ => None,
+        }
+    }
+}
+
+// Whichever meter ran out first, or the clock
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Loss {
+    Stamina,
+    Hull,
+    Fuel,
+    Oxygen,
+    Time,
+}
 
+impl Loss {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Loss::Stamina => "The seal ran out of energy",
+            Loss::Hull => "The submarine flooded",
+            Loss::Fuel => "The submarine ran out of fuel",
+            Loss::Oxygen => "The seal ran out of air",
+            Loss::Time => "Time's up",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Attr {
     pub stamina: f32,
     pub hull: f32,
     pub fuel: f32,
-    pub lost: bool,
+    pub oxygen: f32,
+    // How quickly the hull is flooding through leaks, per tick
+    pub flooding: f32,
+    pub lost: Option<Loss>,
 
     pub score: f32,
 }
@@ -32,18 +124,49 @@ impl Attr {
             stamina: 1.0,
             hull: 1.0,
             fuel: 1.0,
-            lost: false,
+            oxygen: 1.0,
+            flooding: 0.0,
+            lost: None,
             score: 0.0,
         }
     }
 
-    pub fn tick(&mut self, time: f32) {
-        self.stamina = (self.stamina - (0.0001 + 0.0000025 * time)).max(0.0).min(1.0);
-        self.hull = (self.hull - (0.0001 + 0.0000025 * time)).max(0.0).min(1.0);
-        self.fuel = (self.fuel - (0.0001 + 0.0000025 * time)).max(0.0).min(1.0);
+    pub fn is_drained(&self) -> bool {
+        self.stamina <= 0.0 || self.hull <= 0.0 || self.fuel <= 0.0 || self.oxygen <= 0.0
+    }
+
+    pub fn tick(&mut self, time: f32, tuning: &Tuning, mode: GameMode) {
+        if let GameMode::Zen = mode {
+            *self = Self {
+                score: self.score,
+                ..Self::new()
+            };
+            return;
+        }
 
-        if self.stamina <= 0.0 || self.hull <= 0.0 || self.fuel <= 0.0 {
-            self.lost = true;
+        let decay = tuning.decay + tuning.decay_growth * time;
+        self.stamina = (self.stamina - decay).max(0.0).min(1.0);
+        self.hull = (self.hull - self.flooding).max(0.0).min(1.0);
+        self.fuel = (self.fuel - decay).max(0.0).min(1.0);
+        self.oxygen = self.oxygen.clamp(0.0, 1.0);
+
+        if self.lost.is_none() {
+            // Timed runs always last their full length, and empty meters just stop the score going up
+            self.lost = if let Some(limit) = mode.time_limit() {
+                // Counted in whole ticks, since the time in seconds gets rounded and can land just short of the limit
+                let ticks = (time * TICKS_PER_SECOND as f32).round() as u32 + 1;
+                if ticks >= (limit * TICKS_PER_SECOND as f32) as u32 { Some(Loss::Time) } else { None }
+            } else if self.stamina <= 0.0 {
+                Some(Loss::Stamina)
+            } else if self.hull <= 0.0 {
+                Some(Loss::Hull)
+            } else if self.fuel <= 0.0 {
+                Some(Loss::Fuel)
+            } else if self.oxygen <= 0.0 {
+                Some(Loss::Oxygen)
+            } else {
+                None
+            };
         }
     }
 }
@@ -53,9 +176,15 @@ pub enum Event {
     Splash(f32),
     GetFuel,
     GetTape,
+    Bite,
+    Ram,
+    Sting,
+    Clang,
+    Explode(f32),
+    Breathe,
 }
 
-pub fn create() -> (Globals, specs::World) {
+pub fn setup() -> specs::World {
     let mut world = specs::World::new();
 
     world.register::<Pos>();
@@ -65,11 +194,35 @@ pub fn create() -> (Globals, specs::World) {
     world.register::<Agent>();
     world.register::<Body>();
     world.register::<Item>();
+    world.register::<Hazard>();
     world.register::<Respawn>();
     world.register::<Collected>();
+    world.register::<Route>();
+    world.register::<Leak>();
+    world.register::<Pursuit>();
+    world.register::<SaveMarker>();
+
+    world.insert(SimpleMarkerAllocator::<Saved>::new());
+    world.insert(Inputs::default());
+    world.insert(Time::default());
+    world.insert(TickInfo::default());
+    world.insert(SpatialGrid::<Entity>::default());
+
+    world
+}
+
+pub fn create(seed: u64, tuning: Tuning, mode: GameMode) -> (Globals, specs::World) {
+    let mut world = setup();
+    let mut rng = Pcg32::seed_from_u64(seed);
+    let spawns = tuning.spawns.clone();
+    // Zen mode has nothing that can hurt the seal or the submarine
+    let dangers = mode != GameMode::Zen;
 
-    world.insert(Seafloor::sine());
+    world.insert(Seafloor::new(seed));
     world.insert(Attr::new());
+    world.insert(tuning.currents.clone());
+    world.insert(tuning);
+    world.insert(mode);
 
     let player = world
         .create_entity()
@@ -79,6 +232,7 @@ pub fn create() -> (Globals, specs::World) {
         .with(Rot(0.0))
         .with(Agent::Player)
         .with(Body::Seal)
+        .marked::<SaveMarker>()
         .build();
 
     let submarine = world
@@ -87,15 +241,29 @@ pub fn create() -> (Globals, specs::World) {
         .with(Vel(Vec2::unit_x()))
         .with(Ori(0.0))
         .with(Rot(0.0))
+        .with(Agent::Submarine)
         .with(Body::Submarine)
+        .with(Route::new(Vec2::new(0.0, 512.0)))
+        .marked::<SaveMarker>()
         .build();
 
-    for i in 0..30 {
+    if dangers {
+        let leak = Leak::random(&mut rng, 0.0);
+        world
+            .create_entity()
+            .with(Pos(Vec2::new(0.0, 512.0) + leak.offset))
+            .with(Body::Leak)
+            .with(leak)
+            .marked::<SaveMarker>()
+            .build();
+    }
+
+    for i in 0..spawns.fish {
         world
             .create_entity()
             .with(Pos(Vec2::new(
-                thread_rng().gen_range(-3000.0, 3000.0),
-                thread_rng().gen_range(0.0, 1500.0),
+                rng.gen_range(-3000.0, 3000.0),
+                rng.gen_range(0.0, 1500.0),
             )))
             .with(Vel(Vec2::zero()))
             .with(Ori(0.0))
@@ -103,15 +271,16 @@ pub fn create() -> (Globals, specs::World) {
             .with(Agent::Fish)
             .with(Body::Fish(i))
             .with(Item::Fish)
+            .marked::<SaveMarker>()
             .build();
     }
 
-    for i in 0..15 {
+    for i in 0..spawns.fuel {
         world
             .create_entity()
             .with(Pos(Vec2::new(
-                thread_rng().gen_range(-3000.0, 3000.0),
-                thread_rng().gen_range(0.0, 1500.0),
+                rng.gen_range(-3000.0, 3000.0),
+                rng.gen_range(0.0, 1500.0),
             )))
             .with(Vel(Vec2::zero()))
             .with(Ori(0.0))
@@ -119,12 +288,16 @@ pub fn create() -> (Globals, specs::World) {
             .with(Agent::Collectable)
             .with(Body::Fuel(i))
             .with(Item::Fuel)
+            .marked::<SaveMarker>()
             .build();
+    }
+
+    for i in 0..spawns.tape {
         world
             .create_entity()
             .with(Pos(Vec2::new(
-                thread_rng().gen_range(-3000.0, 3000.0),
-                thread_rng().gen_range(0.0, 1500.0),
+                rng.gen_range(-3000.0, 3000.0),
+                rng.gen_range(0.0, 1500.0),
             )))
             .with(Vel(Vec2::zero()))
             .with(Ori(0.0))
@@ -132,30 +305,86 @@ pub fn create() -> (Globals, specs::World) {
             .with(Agent::Collectable)
             .with(Body::Tape(i))
             .with(Item::Tape)
+            .marked::<SaveMarker>()
             .build();
     }
 
-    for i in 0..100 {
+    for i in 0..spawns.bubbles {
         world
             .create_entity()
             .with(Pos(Vec2::new(
-                thread_rng().gen_range(-4000.0, 4000.0),
-                thread_rng().gen_range(0.0, 1500.0),
+                rng.gen_range(-4000.0, 4000.0),
+                rng.gen_range(0.0, 1500.0),
             )))
             .with(Vel(Vec2::zero()))
             .with(Ori(0.0))
             .with(Rot(0.0))
             .with(Agent::Bubble)
             .with(Body::Bubble(i))
+            .marked::<SaveMarker>()
             .build();
     }
 
-    (Globals {
+    let predators = (0..spawns.sharks)
+        .map(|_| Body::Shark)
+        .chain((0..spawns.orcas).map(|_| Body::Orca))
+        .filter(|_| dangers);
+    for body in predators {
+        // Predators start out well away from the seal
+        let side = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+        world
+            .create_entity()
+            .with(Pos(Vec2::new(
+                side * rng.gen_range(1500.0, 3000.0),
+                rng.gen_range(400.0, 1200.0),
+            )))
+            .with(Vel(Vec2::zero()))
+            .with(Ori(0.0))
+            .with(Rot(0.0))
+            .with(Agent::Predator)
+            .with(body)
+            .with(Pursuit::default())
+            .marked::<SaveMarker>()
+            .build();
+    }
+
+    let hazards = (0..spawns.mines)
+        .map(|_| (Body::Mine, Hazard::Mine))
+        .chain((0..spawns.jellyfish).map(|i| (Body::Jellyfish(i), Hazard::Jellyfish)))
+        .chain((0..spawns.debris).map(|i| (Body::Debris(i), Hazard::Debris)))
+        .filter(|_| dangers);
+    for (body, hazard) in hazards {
+        world
+            .create_entity()
+            .with(Pos(Vec2::new(
+                rng.gen_range(-4000.0, 4000.0),
+                rng.gen_range(200.0, 1500.0),
+            )))
+            .with(Vel(Vec2::zero()))
+            .with(Ori(0.0))
+            .with(Rot(0.0))
+            .with(Agent::Hazard)
+            .with(body)
+            .with(hazard)
+            .marked::<SaveMarker>()
+            .build();
+    }
+
+    let globals = Globals {
         player,
         submarine,
-    }, world)
+    };
+
+    world.insert(Seed(seed));
+    world.insert(WorldRng(rng));
+    world.insert(globals);
+
+    (globals, world)
 }
 
+pub const TICKS_PER_SECOND: u32 = 60;
+pub const TICK_DURATION: f32 = 1.0 / TICKS_PER_SECOND as f32;
+
 #[derive(Default)]
 pub struct TickInfo {
     pub view_centre: Vec2<f32>,
@@ -163,253 +392,45 @@ pub struct TickInfo {
     pub events: Vec<Event>,
 }
 
-const TURN_RATE_WATER: f32 = 0.0065;
-const TURN_RATE_AIR: f32 = 0.001;
-const GRAVITY: f32 = 0.1;
-
-pub fn tick(world: &specs::World, inputs: Inputs, time: f32, globals: &Globals) -> TickInfo {
-    let mut tick_info = TickInfo::default();
-
-    let underwater = |pos: &Pos| pos.0.y > 0.0;
-
-    let seafloor = world.read_resource::<Seafloor>();
-    let mut attr = world.write_resource::<Attr>();
-
-    // Physics
-    for (pos, vel, ori, rot) in (
-        &mut world.write_storage::<Pos>(),
-        &mut world.write_storage::<Vel>(),
-        &mut world.write_storage::<Ori>(),
-        &mut world.write_storage::<Rot>(),
-    ).join() {
-        // Drag
-        if underwater(pos) {
-            // Drag
-            vel.0 *= 0.95;
-            rot.0 *= 0.90;
-
-            let ori_dir = Vec2::new(
-                ori.0.cos(),
-                ori.0.sin(),
-            );
-            vel.0 *= f32::lerp(ori_dir.dot(vel.0.try_normalized().unwrap_or(Vec2::zero())).max((-ori_dir).dot(vel.0.try_normalized().unwrap_or(Vec2::zero()))), 1.0, 0.9);
-        } else {
-            rot.0 *= 0.98;
-        };
-
-        if !underwater(pos) {
-            vel.0.y += GRAVITY;
-        }
-
-        let is_underwater = underwater(pos);
-        pos.0 += vel.0;
-        let is_underwater2 = underwater(pos);
-
-        if is_underwater ^ is_underwater2 && vel.0.y.abs() > 4.0 {
-            tick_info.events.push(Event::Splash(pos.0.x));
-        }
-
-        ori.0 += rot.0;
-
-        // Collision with seafloor
-        while pos.0.y > seafloor.sample(pos.0.x) {
-            pos.0 += seafloor.normal_at(pos.0.x) * 0.5;
-            vel.0 += seafloor.normal_at(pos.0.x) * 0.1;
-            vel.0 *= 0.97;
-        }
-
-    }
-
-    // Find all positions and velocities
-    let mut all_pos_vel = (
-        &world.read_storage::<Pos>(),
-        &world.read_storage::<Vel>(),
-        &world.read_storage::<Agent>(),
-    )
-        .join()
-        .filter(|(_, _, agent)| if let Agent::Fish = agent { true } else { false })
-        .map(|(p, v, _)| (p.0, v.0))
-        .collect::<Vec<_>>();
-
-    // Control
-    for (entity, pos, vel, ori, rot, agent) in (
-        &world.entities(),
-        &world.read_storage::<Pos>(),
-        &mut world.write_storage::<Vel>(),
-        &mut world.write_storage::<Ori>(),
-        &mut world.write_storage::<Rot>(),
-        &mut world.write_storage::<Agent>(),
-    ).join() {
-        let physics = match agent {
-            Agent::Player => {
-                // User input
-                let score_multi = if underwater(pos) {
-                    if inputs.left { rot.0 -= TURN_RATE_WATER; }
-                    if inputs.right { rot.0 += TURN_RATE_WATER; }
-
-                    if inputs.boost { vel.0 *= 1.025; attr.stamina -= 0.0003; }
-                    1.0
-                } else {
-                    if inputs.left { rot.0 -= TURN_RATE_AIR; }
-                    if inputs.right { rot.0 += TURN_RATE_AIR; }
-                    2.0
-                };
-
-                attr.score += (0.03 + attr.score.powf(0.5) * 0.001) * score_multi;
-
-                // Tick info
-                tick_info.view_centre = pos.0;
-                tick_info.view_scale = 1.0;// + 1.0 / (1.0 + vel.0.magnitude());
-
-                true
-            },
-            Agent::Fish => {
-                all_pos_vel.sort_by_key(|(other_pos, _)| pos.0.distance_squared(*other_pos) as i32);
-
-                let (total_vel, n) = all_pos_vel.iter().take(3).fold((Vec2::zero(), 0.0), |(tv, n), (_, v)| (tv + v, n + 1.0));
-                let avg_vel = total_vel / n;
-
-                let (total_pos, n) = all_pos_vel.iter().take(3).fold((Vec2::zero(), 0.0), |(tp, n), (p, _)| (tp + p, n + 1.0));
-                let avg_pos = total_pos / n;
-
-                let shy_dir = all_pos_vel.iter().take(3).fold(Vec2::zero(), |a, (p, _)| a + (pos.0 - p).try_normalized().unwrap_or(Vec2::zero()) * (100.0 - (pos.0 - p).magnitude()).max(0.0));
-                let dir = Vec2::lerp((avg_pos - pos.0).normalized(), avg_vel, 0.75) + shy_dir * 0.15;
-                let dir = (dir.map(|e| e + thread_rng().gen_range(-0.05, 0.05)) - (pos.0 - Vec2::new(0.0, 650.0)) * Vec2::new(1.0, 4.5) * 0.0003).normalized();
-
-                let dir = Lerp::lerp(Vec2::new(ori.0.cos(), ori.0.sin()), dir, 0.1).try_normalized().unwrap_or(Vec2::zero());
-                ori.0 = dir.y.atan2(dir.x);
-
-                true
-            },
-            Agent::Bubble => {
-                vel.0.x = (time + pos.0.x * 0.01).sin();
-                vel.0.y = -1.0;
-
-                if !underwater(pos) {
-                    world.write_storage().insert(entity, Respawn);
-                }
-
-                false
-            },
-            Agent::Collectable => {
-                if world.read_storage::<Collected>().get(entity).is_some() {
-                    let dir = (world.read_storage::<Pos>().get(globals.player).map(|p| p.0).unwrap_or(Vec2::zero()) - pos.0).try_normalized().unwrap_or(Vec2::unit_y());
-                    ori.0 = dir.y.atan2(dir.x);
-                    if underwater(pos) {
-                        vel.0 *= 1.02;
-                    } else {
-                        vel.0 *= 0.98;
-                    }
-                } else {
-                    vel.0 = Vec2::zero();
-                }
-
-                true
-            },
-        };
-
-        // Swimming
-        if underwater(pos) && physics {
-            vel.0 += Vec2::new(
-                ori.0.cos(),
-                ori.0.sin(),
-            ) * 0.3;
+pub fn tick(world: &mut specs::World, dispatcher: &mut Dispatcher, inputs: Inputs, time: f32) -> TickInfo {
+    world.insert(inputs);
+    world.insert(Time(time));
 
-            rot.0 += (time * 10.0).sin() * vel.0.magnitude().sqrt() * 0.001;
-        }
-    }
-
-    // Collision
-    for (entity, pos, agent, body) in (
-        &world.entities(),
-        &world.read_storage::<Pos>(),
-        &world.read_storage::<Agent>(),
-        &world.read_storage::<Body>(),
-    ).join() {
-        if let Agent::Player = agent {
-            for (other_entity, other_pos, item, body) in (
-                &world.entities(),
-                &world.read_storage::<Pos>(),
-                &world.read_storage::<Item>(),
-                &world.read_storage::<Body>(),
-            )
-                .join()
-                .filter(|(_, op, _, other_body)| op.0.distance_squared(pos.0) < (body.radius() + other_body.radius()).powf(2.0))
-            {
-                match item {
-                    Item::Fish => {
-                        world.write_storage().insert(other_entity, Respawn);
-                        attr.stamina += FISH_STAMINA;
-                        tick_info.events.push(Event::Eat);
-                    },
-                    Item::Fuel | Item::Tape => {
-                        world.write_storage().insert(other_entity, Collected);
-                    },
-                }
-            }
-        }
+    dispatcher.dispatch(world);
+    world.maintain();
 
-        if let Body::Fuel(_) = body {
-            if ((world.read_storage::<Pos>().get(globals.submarine).map(|p| p.0).unwrap_or(Vec2::zero()) - pos.0) * Vec2::new(1.0, 3.0)).magnitude_squared() < 500.0f32.powf(2.0) {
-                world.write_storage().insert(entity, Respawn);
-                attr.fuel += FUEL_BOOST;
-                tick_info.events.push(Event::GetFuel);
-            }
-        }
-
-        if let Body::Tape(_) = body {
-            if ((world.read_storage::<Pos>().get(globals.submarine).map(|p| p.0).unwrap_or(Vec2::zero()) - pos.0) * Vec2::new(1.0, 3.0)).magnitude_squared() < 500.0f32.powf(2.0) {
-                world.write_storage().insert(entity, Respawn);
-                attr.hull += TAPE_BOOST;
-                tick_info.events.push(Event::GetTape);
-            }
-        }
-    }
-
-    // Respawn things
-    for (entity, pos, _) in (
-        &world.entities(),
-        &mut world.write_storage::<Pos>(),
-        &world.read_storage::<Respawn>(),
-    ).join() {
-        pos.0 = Vec2::new(
-            thread_rng().gen_range(-3500.0, 3500.0),
-            thread_rng().gen_range(-300.0, 1500.0),
-        );
-        world.write_storage::<Collected>().remove(entity);
-    }
-    world.write_storage::<Respawn>().clear();
-
-    // Tick global attributes
-    attr.tick(time);
-
-    tick_info
+    std::mem::take(&mut *world.write_resource::<TickInfo>())
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Pos(pub Vec2<f32>);
 
 impl Component for Pos {
     type Storage = VecStorage<Self>;
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Vel(pub Vec2<f32>);
 
 impl Component for Vel {
     type Storage = VecStorage<Self>;
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Ori(pub f32);
 
 impl Component for Ori {
     type Storage = VecStorage<Self>;
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Rot(pub f32);
 
 impl Component for Rot {
     type Storage = VecStorage<Self>;
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Item {
     Fish,
     Fuel,
@@ -420,6 +441,20 @@ impl Component for Item {
     type Storage = VecStorage<Self>;
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Hazard {
+    // Drifts until pushed, and damages the hull if it gets knocked into the submarine
+    Mine,
+    // Stings the seal on contact
+    Jellyfish,
+    // Sinks from above, knocking the seal and denting the hull
+    Debris,
+}
+
+impl Component for Hazard {
+    type Storage = VecStorage<Self>;
+}
+
 #[derive(Default)]
 pub struct Respawn;
 
@@ -427,17 +462,25 @@ impl Component for Respawn {
     type Storage = NullStorage<Self>;
 }
 
-#[derive(Default)]
-pub struct Collected;
+// Braced so that it saves as {} and not null, which specs would load back as no component at all
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Collected {}
 
 impl Component for Collected {
     type Storage = NullStorage<Self>;
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Body {
     Seal,
     Fish(usize),
     Submarine,
+    Leak,
+    Shark,
+    Orca,
+    Mine,
+    Jellyfish(usize),
+    Debris(usize),
     Bubble(usize),
     Fuel(usize),
     Tape(usize),
@@ -453,6 +496,12 @@ impl Body {
             Body::Seal => 20.0,
             Body::Fish(_) => 12.0,
             Body::Submarine => 800.0,
+            Body::Leak => 40.0,
+            Body::Shark => 40.0,
+            Body::Orca => 70.0,
+            Body::Mine => 28.0,
+            Body::Jellyfish(_) => 24.0,
+            Body::Debris(_) => 30.0,
             Body::Bubble(_) => 12.0,
             Body::Fuel(_) => 20.0,
             Body::Tape(_) => 20.0,
@@ -460,42 +509,132 @@ impl Body {
     }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Agent {
     Player,
+    Submarine,
     Fish,
     Bubble,
     Collectable,
+    Predator,
+    Hazard,
 }
 
 impl Component for Agent {
     type Storage = VecStorage<Self>;
 }
 
+// Where an agent is heading, and the waypoints after that
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Route {
+    pub waypoints: VecDeque<Vec2<f32>>,
+    pub heading: f32,
+}
+
+impl Route {
+    pub fn new(start: Vec2<f32>) -> Self {
+        Self {
+            waypoints: std::iter::once(start).collect(),
+            heading: 1.0,
+        }
+    }
+}
+
+impl Component for Route {
+    type Storage = HashMapStorage<Self>;
+}
+
+// Predators back off for a while after going for the seal
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Pursuit {
+    pub rest: u32,
+}
+
+impl Component for Pursuit {
+    type Storage = HashMapStorage<Self>;
+}
+
+// A hole in the submarine's hull, relative to its centre when facing right
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Leak {
+    pub offset: Vec2<f32>,
+    pub rate: f32,
+}
+
+impl Leak {
+    pub fn random(rng: &mut impl Rng, time: f32) -> Self {
+        // Somewhere around the edge of the hull
+        let angle = rng.gen_range(0.0, std::f32::consts::PI * 2.0);
+        Self {
+            offset: Vec2::new(angle.cos() * 400.0, 56.0 + angle.sin() * 110.0),
+            rate: 0.0001 + 0.0000015 * time,
+        }
+    }
+}
+
+impl Component for Leak {
+    type Storage = HashMapStorage<Self>;
+}
+
+// An endless seafloor made from seeded noise. Heights get cached in chunks around the player as they move, but any
+// part of the floor can be sampled at any time.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Seafloor {
-    heights: Vec<f32>,
+    seed: u64,
+    #[serde(skip)]
+    chunks: HashMap<i32, Vec<f32>>,
 }
 
 const SEAFLOOR_HEIGHT: f32 = 1500.0;
+const SEAFLOOR_MIN_HEIGHT: f32 = 700.0;
 const SEAFLOOR_STRIDE: f32 = 10.0;
-const SEAFLOOR_OFFSET: i32 = 700;
+const SEAFLOOR_CHUNK: i32 = 64;
 
 impl Seafloor {
-    pub fn sine() -> Self {
+    pub fn new(seed: u64) -> Self {
         Self {
-            heights: (-SEAFLOOR_OFFSET..700)
-                .map(|i| i as f32 * SEAFLOOR_STRIDE)
-                .map(|x| SEAFLOOR_HEIGHT + (x * 0.01).sin() * 30.0 + (x * 0.002).sin() * 120.0 - x.abs() * 0.35)
-                .collect(),
+            seed,
+            chunks: HashMap::new(),
+        }
+    }
+
+    fn height_at(&self, x: f32) -> f32 {
+        let hills = fbm(self.seed, x / 3000.0, 4) * 900.0;
+        let bumps = fbm(self.seed.wrapping_add(1), x / 300.0, 3) * 60.0;
+        (SEAFLOOR_HEIGHT + hills + bumps + (x * 0.01).sin() * 30.0).max(SEAFLOOR_MIN_HEIGHT)
+    }
+
+    fn point(&self, idx: i32) -> f32 {
+        self.chunks
+            .get(&idx.div_euclid(SEAFLOOR_CHUNK))
+            .map(|chunk| chunk[idx.rem_euclid(SEAFLOOR_CHUNK) as usize])
+            .unwrap_or_else(|| self.height_at(idx as f32 * SEAFLOOR_STRIDE))
+    }
+
+    // Make sure that the floor within the given range is cached, and forget about chunks far outside it
+    pub fn generate_around(&mut self, x: f32, range: f32) {
+        let chunk_width = SEAFLOOR_CHUNK as f32 * SEAFLOOR_STRIDE;
+        let min = ((x - range) / chunk_width).floor() as i32;
+        let max = ((x + range) / chunk_width).floor() as i32;
+
+        self.chunks.retain(|chunk, _| *chunk >= min - 4 && *chunk <= max + 4);
+
+        for chunk in min..=max {
+            if !self.chunks.contains_key(&chunk) {
+                let heights = (0..SEAFLOOR_CHUNK)
+                    .map(|i| self.height_at((chunk * SEAFLOOR_CHUNK + i) as f32 * SEAFLOOR_STRIDE))
+                    .collect();
+                self.chunks.insert(chunk, heights);
+            }
         }
     }
 
     pub fn sample(&self, x: f32) -> f32 {
-        let xx = x + SEAFLOOR_OFFSET as f32 * SEAFLOOR_STRIDE;
-        let fract = (xx / SEAFLOOR_STRIDE).fract();
-        let idx = (xx / SEAFLOOR_STRIDE) as usize;
+        let idx = (x / SEAFLOOR_STRIDE).floor();
+        let fract = x / SEAFLOOR_STRIDE - idx;
 
-        let a = self.heights.get(idx).copied().unwrap_or(0.0);
-        let b = self.heights.get(idx.saturating_add(1)).copied().unwrap_or(0.0);
+        let a = self.point(idx as i32);
+        let b = self.point(idx as i32 + 1);
 
         a + (b - a) * fract
     }
@@ -507,3 +646,144 @@ impl Seafloor {
         ).try_normalized().unwrap_or(-Vec2::unit_y())
     }
 }
+
+// A body of water that flows one way. Its strength swings back and forth over `period` seconds (or holds steady when
+// that's zero), and fades out over `feather` units towards its edges.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Current {
+    pub min: Vec2<f32>,
+    pub max: Vec2<f32>,
+    // Lay the region out again every this many units along x, for an endless sea
+    pub repeat: Option<f32>,
+    pub flow: Vec2<f32>,
+    pub period: f32,
+    pub feather: f32,
+}
+
+impl Current {
+    fn sample(&self, pos: Vec2<f32>, time: f32) -> Vec2<f32> {
+        let x = match self.repeat {
+            Some(repeat) => self.min.x + (pos.x - self.min.x).rem_euclid(repeat),
+            None => pos.x,
+        };
+        let edge = (x - self.min.x)
+            .min(self.max.x - x)
+            .min(pos.y - self.min.y)
+            .min(self.max.y - pos.y);
+        if edge <= 0.0 {
+            return Vec2::zero();
+        }
+
+        let fade = if self.feather > 0.0 { (edge / self.feather).min(1.0) } else { 1.0 };
+        let swing = if self.period > 0.0 { (time * std::f32::consts::PI * 2.0 / self.period).sin() } else { 1.0 };
+        self.flow * fade * swing
+    }
+}
+
+// How fast the water is moving anywhere in the sea, in units per tick. Regions overlap by adding together.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OceanCurrents {
+    pub regions: Vec<Current>,
+}
+
+impl OceanCurrents {
+    pub fn sample(&self, pos: Vec2<f32>, time: f32) -> Vec2<f32> {
+        self.regions
+            .iter()
+            .map(|current| current.sample(pos, time))
+            .sum()
+    }
+}
+
+impl Default for OceanCurrents {
+    fn default() -> Self {
+        let endless = 1.0e9;
+        Self {
+            regions: vec![
+                // A surface drift that turns with the tide
+                Current {
+                    min: Vec2::new(-endless, 0.0),
+                    max: Vec2::new(endless, 350.0),
+                    repeat: None,
+                    flow: Vec2::new(0.8, 0.0),
+                    period: 60.0,
+                    feather: 150.0,
+                },
+                // A steady flow the other way down in the deep
+                Current {
+                    min: Vec2::new(-endless, 900.0),
+                    max: Vec2::new(endless, endless),
+                    repeat: None,
+                    flow: Vec2::new(-1.0, 0.0),
+                    period: 0.0,
+                    feather: 300.0,
+                },
+                // Updrafts every so often, for a quick ride up to the surface
+                Current {
+                    min: Vec2::new(0.0, 200.0),
+                    max: Vec2::new(500.0, endless),
+                    repeat: Some(6000.0),
+                    flow: Vec2::new(0.0, -2.0),
+                    period: 0.0,
+                    feather: 150.0,
+                },
+            ],
+        }
+    }
+}
+
+// A random value in -1..1 for each integer coordinate
+fn lattice(seed: u64, i: i32) -> f32 {
+    let mut z = seed ^ (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+}
+
+// 1D gradient noise, roughly in -1..1
+fn noise(seed: u64, x: f32) -> f32 {
+    let i = x.floor();
+    let t = x - i;
+    let a = lattice(seed, i as i32) * t;
+    let b = lattice(seed, i as i32 + 1) * (t - 1.0);
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    (a + (b - a) * fade) * 2.0
+}
+
+fn fbm(seed: u64, x: f32, octaves: u32) -> f32 {
+    (0..octaves)
+        .map(|o| noise(seed.wrapping_add(o as u64), x * (1 << o) as f32) / (1 << o) as f32)
+        .sum::<f32>() * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::systems;
+
+    // The attributes and every entity's position after a run, which should only depend on the seed and the inputs
+    fn run(seed: u64) -> (String, Vec<String>) {
+        let (_, mut world) = create(seed, Tuning::default(), GameMode::Endless);
+        let mut dispatcher = systems::dispatcher();
+        for t in 0..2000u32 {
+            let inputs = Inputs::new(((t / 40) % 3) as f32 - 1.0, ((t / 100) % 2) as f32);
+            tick(&mut world, &mut dispatcher, inputs, t as f32 * TICK_DURATION);
+        }
+
+        let entities = world.entities();
+        let positions = world.read_storage::<Pos>();
+        let positions = (&entities, &positions).join().map(|(entity, pos)| format!("{:?} {:?}", entity, pos.0)).collect();
+        (format!("{:?}", *world.read_resource::<Attr>()), positions)
+    }
+
+    #[test]
+    fn same_seed_and_inputs_give_the_same_world() {
+        assert_eq!(run(42), run(42));
+    }
+
+    #[test]
+    fn different_seeds_give_different_worlds() {
+        assert_ne!(run(42).1, run(43).1);
+    }
+}